@@ -0,0 +1,239 @@
+//! Approval requests: a Block Kit message with Approve and Reject buttons,
+//! and the `block_actions` interaction that answers it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::events::SlackEvent;
+use crate::markdown;
+
+/// How long request_approval waits when no timeout is given.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+const APPROVE_ACTION: &str = "approval_approve";
+const REJECT_ACTION: &str = "approval_reject";
+const COMMENT_BLOCK: &str = "approval_comment";
+const COMMENT_ACTION: &str = "comment";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub approved: bool,
+    pub user_id: String,
+    pub user_name: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// An ID unique to this process, carried in the buttons' `value`.
+pub fn new_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    let now = chrono::Utc::now().timestamp_millis();
+    format!("{}-{}", now, NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// The request message: the question, optional details, a comment box and
+/// the two buttons.
+pub fn request_blocks(id: &str, question: &str, details: Option<&str>) -> Vec<Value> {
+    let mut blocks = vec![json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": format!("*Approval requested:* {}", question) },
+    })];
+    if let Some(details) = details.filter(|d| !d.is_empty()) {
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": details },
+        }));
+    }
+    blocks.push(json!({
+        "type": "input",
+        "block_id": COMMENT_BLOCK,
+        "optional": true,
+        "label": { "type": "plain_text", "text": "Comment" },
+        "element": { "type": "plain_text_input", "action_id": COMMENT_ACTION },
+    }));
+    blocks.push(json!({
+        "type": "actions",
+        "block_id": format!("approval:{}", id),
+        "elements": [
+            {
+                "type": "button",
+                "action_id": APPROVE_ACTION,
+                "style": "primary",
+                "text": { "type": "plain_text", "text": "Approve" },
+                "value": id,
+            },
+            {
+                "type": "button",
+                "action_id": REJECT_ACTION,
+                "style": "danger",
+                "text": { "type": "plain_text", "text": "Reject" },
+                "value": id,
+            },
+        ],
+    }));
+    blocks
+}
+
+/// What the message turns into once answered (or not): the question and a
+/// line saying what happened, without the buttons.
+pub fn outcome_blocks(question: &str, outcome: &str) -> Vec<Value> {
+    vec![
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("*Approval requested:* {}", question) },
+        }),
+        json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": outcome }],
+        }),
+    ]
+}
+
+pub fn outcome_text(decision: Option<&Decision>) -> String {
+    match decision {
+        Some(d) => {
+            let verdict = if d.approved {
+                ":white_check_mark: Approved"
+            } else {
+                ":x: Rejected"
+            };
+            let mut text = format!("{} by <@{}>", verdict, d.user_id);
+            if let Some(comment) = &d.comment {
+                text.push_str(&format!(": {}", markdown::escape(comment)));
+            }
+            text
+        }
+        None => ":hourglass: Expired without an answer".to_string(),
+    }
+}
+
+/// Read a `block_actions` payload as an answer to request `id`, posted as
+/// message `ts` in `channel`. Clicks on any other message, even with the
+/// same value, and from users outside `approvers` (when given) do not count.
+pub fn decision(
+    payload: &Value,
+    id: &str,
+    channel: &str,
+    ts: &str,
+    approvers: &[String],
+) -> Option<Decision> {
+    if payload["type"] != "block_actions"
+        || payload["container"]["message_ts"] != ts
+        || payload["channel"]["id"] != channel
+    {
+        return None;
+    }
+    let action = payload["actions"]
+        .as_array()?
+        .iter()
+        .find(|a| a["value"] == id)?;
+    let approved = match action["action_id"].as_str()? {
+        APPROVE_ACTION => true,
+        REJECT_ACTION => false,
+        _ => return None,
+    };
+    let user_id = payload["user"]["id"].as_str()?.to_string();
+    if !approvers.is_empty() && !approvers.contains(&user_id) {
+        tracing::info!("Ignoring approval click from {}, who is not an approver", user_id);
+        return None;
+    }
+    let user_name = ["username", "name"]
+        .iter()
+        .find_map(|k| payload["user"][k].as_str())
+        .map(String::from);
+    let comment = payload["state"]["values"][COMMENT_BLOCK][COMMENT_ACTION]["value"]
+        .as_str()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(String::from);
+    Some(Decision {
+        approved,
+        user_id,
+        user_name,
+        comment,
+    })
+}
+
+/// Wait on `rx` (subscribed before the request was posted) for the first
+/// qualifying answer to request `id` at `ts` in `channel`. Returns `None` if
+/// the bus shuts down.
+pub async fn wait(
+    mut rx: broadcast::Receiver<SlackEvent>,
+    id: &str,
+    channel: &str,
+    ts: &str,
+    approvers: &[String],
+) -> Option<Decision> {
+    loop {
+        match rx.recv().await {
+            Ok(SlackEvent::Interaction(payload)) => {
+                if let Some(decision) = decision(&payload, id, channel, ts, approvers) {
+                    return Some(decision);
+                }
+            }
+            Ok(_) => {}
+            // Clicks are not in any history to catch up from.
+            Err(RecvError::Lagged(n)) => tracing::warn!(
+                "Approval request {} missed {} events; a click may have been lost",
+                id,
+                n
+            ),
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(action_id: &str, value: &str, user: &str, comment: Option<&str>) -> Value {
+        json!({
+            "type": "block_actions",
+            "user": { "id": user, "username": "alice" },
+            "channel": { "id": "C001" },
+            "container": { "type": "message", "message_ts": "1.000001", "channel_id": "C001" },
+            "actions": [{ "action_id": action_id, "value": value, "type": "button" }],
+            "state": { "values": { COMMENT_BLOCK: { COMMENT_ACTION: { "value": comment } } } },
+        })
+    }
+
+    #[test]
+    fn reads_decisions_from_block_actions() {
+        let approvers = vec!["U001".to_string()];
+        let read = |payload: &Value, approvers: &[String]| {
+            decision(payload, "id-1", "C001", "1.000001", approvers)
+        };
+        let approved = read(&click(APPROVE_ACTION, "id-1", "U001", Some(" ok ")), &approvers).unwrap();
+        assert!(approved.approved);
+        assert_eq!(approved.comment.as_deref(), Some("ok"));
+        assert_eq!(approved.user_name.as_deref(), Some("alice"));
+
+        let rejected = read(&click(REJECT_ACTION, "id-1", "U001", None), &[]).unwrap();
+        assert!(!rejected.approved);
+        assert_eq!(rejected.comment, None);
+
+        assert_eq!(read(&click(APPROVE_ACTION, "id-2", "U001", None), &[]), None);
+        assert_eq!(read(&click(APPROVE_ACTION, "id-1", "U002", None), &approvers), None);
+
+        // The right value on some other message does not count.
+        let mut elsewhere = click(APPROVE_ACTION, "id-1", "U001", None);
+        elsewhere["container"]["message_ts"] = json!("2.000001");
+        assert_eq!(read(&elsewhere, &[]), None);
+        let mut elsewhere = click(APPROVE_ACTION, "id-1", "U001", None);
+        elsewhere["channel"]["id"] = json!("C002");
+        assert_eq!(read(&elsewhere, &[]), None);
+        assert!(outcome_text(Some(&rejected)).starts_with(":x: Rejected by <@U001>"));
+    }
+
+    #[test]
+    fn request_blocks_carry_the_id() {
+        let blocks = request_blocks("id-1", "Drop the staging DB?", Some("It is 40 GB."));
+        let actions = blocks.last().unwrap();
+        assert_eq!(actions["elements"][0]["value"], "id-1");
+        assert_eq!(actions["elements"][1]["action_id"], REJECT_ACTION);
+        crate::blocks::validate_blocks(&blocks, "blocks").unwrap();
+    }
+}
@@ -0,0 +1,438 @@
+//! Local Block Kit validation, so malformed blocks fail with a path to the bad
+//! block instead of Slack's bare `invalid_blocks`.
+
+use std::borrow::Cow;
+
+use rmcp::model::{ErrorCode, ErrorData};
+use serde_json::{json, Value};
+
+pub const MAX_BLOCKS: usize = 50;
+pub const MAX_SECTION_TEXT: usize = 3000;
+/// Slack truncates message text past this many characters.
+pub const MAX_MESSAGE_TEXT: usize = 40_000;
+const MAX_ATTACHMENTS: usize = 100;
+pub const MAX_HEADER_TEXT: usize = 150;
+const MAX_FIELDS: usize = 10;
+const MAX_FIELD_TEXT: usize = 2000;
+const MAX_CONTEXT_ELEMENTS: usize = 10;
+const MAX_ACTIONS_ELEMENTS: usize = 25;
+const MAX_BUTTON_TEXT: usize = 75;
+const MAX_BLOCK_ID: usize = 255;
+const MAX_MARKDOWN_TEXT: usize = 12_000;
+/// Cap on the generated notification text; it only has to be a preview.
+const MAX_FALLBACK_TEXT: usize = 3000;
+
+const BLOCK_TYPES: &[&str] = &[
+    "actions", "context", "divider", "file", "header", "image", "input", "markdown",
+    "rich_text", "section", "video",
+];
+
+const INTERACTIVE_ELEMENTS: &[&str] = &[
+    "button",
+    "checkboxes",
+    "datepicker",
+    "datetimepicker",
+    "overflow",
+    "radio_buttons",
+    "static_select",
+    "external_select",
+    "users_select",
+    "conversations_select",
+    "channels_select",
+    "multi_static_select",
+    "multi_external_select",
+    "multi_users_select",
+    "multi_conversations_select",
+    "multi_channels_select",
+    "timepicker",
+    "workflow_button",
+];
+
+const INPUT_ELEMENTS: &[&str] = &[
+    "plain_text_input",
+    "email_text_input",
+    "url_text_input",
+    "number_input",
+    "rich_text_input",
+    "file_input",
+];
+
+#[derive(Debug, thiserror::Error)]
+#[error("{path}: {message}")]
+pub struct BlockError {
+    pub path: String,
+    pub message: String,
+}
+
+impl From<BlockError> for ErrorData {
+    fn from(e: BlockError) -> Self {
+        ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from(format!("Invalid Block Kit payload at {}", e)),
+            data: Some(json!({
+                "path": e.path,
+                "hint": "Fix the block at `path` and retry. See https://api.slack.com/reference/block-kit/blocks.",
+            })),
+        }
+    }
+}
+
+fn fail<T>(path: &str, message: impl Into<String>) -> Result<T, BlockError> {
+    Err(BlockError {
+        path: path.to_string(),
+        message: message.into(),
+    })
+}
+
+/// Accept blocks or attachments either as a JSON array or as a JSON-encoded
+/// string of one, which is how Slack's own docs and many clients pass them.
+pub fn parse_array(value: Value, path: &str) -> Result<Vec<Value>, BlockError> {
+    let value = match value {
+        Value::String(s) => match serde_json::from_str(&s) {
+            Ok(v) => v,
+            Err(e) => return fail(path, format!("not valid JSON: {}", e)),
+        },
+        v => v,
+    };
+    match value {
+        Value::Array(items) => Ok(items),
+        _ => fail(path, "expected an array"),
+    }
+}
+
+/// Validate a message's top-level `blocks` array.
+pub fn validate_blocks(blocks: &[Value], path: &str) -> Result<(), BlockError> {
+    if blocks.len() > MAX_BLOCKS {
+        return fail(
+            path,
+            format!("{} blocks; a message can hold at most {}", blocks.len(), MAX_BLOCKS),
+        );
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        validate_block(block, &format!("{}[{}]", path, i))?;
+    }
+    Ok(())
+}
+
+/// Validate legacy attachments, including any blocks nested in them.
+pub fn validate_attachments(attachments: &[Value], path: &str) -> Result<(), BlockError> {
+    if attachments.len() > MAX_ATTACHMENTS {
+        return fail(
+            path,
+            format!("{} attachments; the limit is {}", attachments.len(), MAX_ATTACHMENTS),
+        );
+    }
+    for (i, attachment) in attachments.iter().enumerate() {
+        let path = format!("{}[{}]", path, i);
+        if !attachment.is_object() {
+            return fail(&path, "attachment must be an object");
+        }
+        if let Some(blocks) = attachment.get("blocks") {
+            let path = format!("{}.blocks", path);
+            match blocks.as_array() {
+                Some(blocks) => validate_blocks(blocks, &path)?,
+                None => return fail(&path, "expected an array"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn str_field<'a>(v: &'a Value, key: &str, path: &str) -> Result<&'a str, BlockError> {
+    match v.get(key) {
+        Some(Value::String(s)) => Ok(s),
+        Some(_) => fail(&format!("{}.{}", path, key), "expected a string"),
+        None => fail(path, format!("missing required field `{}`", key)),
+    }
+}
+
+fn array_field<'a>(v: &'a Value, key: &str, path: &str) -> Result<&'a Vec<Value>, BlockError> {
+    match v.get(key) {
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => fail(&format!("{}.{}", path, key), "expected an array"),
+        None => fail(path, format!("missing required field `{}`", key)),
+    }
+}
+
+fn check_len(s: &str, max: usize, path: &str, what: &str) -> Result<(), BlockError> {
+    let len = s.chars().count();
+    if len > max {
+        return fail(path, format!("{} is {} characters; the limit is {}", what, len, max));
+    }
+    Ok(())
+}
+
+/// A `plain_text` or `mrkdwn` composition object. `plain_only` is for fields
+/// Slack restricts to plain text (headers, button labels).
+fn validate_text(v: &Value, path: &str, max: usize, plain_only: bool) -> Result<(), BlockError> {
+    if !v.is_object() {
+        return fail(path, "text must be an object like {\"type\": \"mrkdwn\", \"text\": \"...\"}");
+    }
+    let kind = str_field(v, "type", path)?;
+    match kind {
+        "plain_text" => {}
+        "mrkdwn" if !plain_only => {}
+        "mrkdwn" => return fail(&format!("{}.type", path), "must be plain_text here"),
+        other => {
+            return fail(
+                &format!("{}.type", path),
+                format!("unknown text type `{}`; use plain_text or mrkdwn", other),
+            )
+        }
+    }
+    let text = str_field(v, "text", path)?;
+    if text.is_empty() {
+        return fail(&format!("{}.text", path), "text must not be empty");
+    }
+    check_len(text, max, &format!("{}.text", path), "text")
+}
+
+fn validate_element(v: &Value, path: &str, allowed: &[&str]) -> Result<(), BlockError> {
+    if !v.is_object() {
+        return fail(path, "element must be an object");
+    }
+    let kind = str_field(v, "type", path)?;
+    if !allowed.contains(&kind) {
+        return fail(
+            &format!("{}.type", path),
+            format!("`{}` is not allowed here; expected one of {}", kind, allowed.join(", ")),
+        );
+    }
+    match kind {
+        "button" | "workflow_button" => {
+            validate_text(&v["text"], &format!("{}.text", path), MAX_BUTTON_TEXT, true)?;
+            if let Some(style) = v.get("style").and_then(|s| s.as_str()) {
+                if style != "primary" && style != "danger" {
+                    return fail(
+                        &format!("{}.style", path),
+                        format!("unknown style `{}`; use primary or danger", style),
+                    );
+                }
+            }
+        }
+        "image" => {
+            if v.get("image_url").is_none() && v.get("slack_file").is_none() {
+                return fail(path, "image needs `image_url` or `slack_file`");
+            }
+            str_field(v, "alt_text", path)?;
+        }
+        "plain_text" | "mrkdwn" => validate_text(v, path, MAX_SECTION_TEXT, false)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn validate_block(block: &Value, path: &str) -> Result<(), BlockError> {
+    if !block.is_object() {
+        return fail(path, "block must be an object");
+    }
+    let kind = str_field(block, "type", path)?;
+    if let Some(id) = block.get("block_id").and_then(|b| b.as_str()) {
+        check_len(id, MAX_BLOCK_ID, &format!("{}.block_id", path), "block_id")?;
+    }
+    match kind {
+        "section" => {
+            let text = block.get("text");
+            let fields = block.get("fields");
+            if text.is_none() && fields.is_none() {
+                return fail(path, "section needs `text` or `fields`");
+            }
+            if let Some(text) = text {
+                validate_text(text, &format!("{}.text", path), MAX_SECTION_TEXT, false)?;
+            }
+            if fields.is_some() {
+                let fields = array_field(block, "fields", path)?;
+                if fields.len() > MAX_FIELDS {
+                    return fail(
+                        &format!("{}.fields", path),
+                        format!("{} fields; the limit is {}", fields.len(), MAX_FIELDS),
+                    );
+                }
+                for (i, field) in fields.iter().enumerate() {
+                    validate_text(field, &format!("{}.fields[{}]", path, i), MAX_FIELD_TEXT, false)?;
+                }
+            }
+            if let Some(accessory) = block.get("accessory") {
+                let mut allowed = INTERACTIVE_ELEMENTS.to_vec();
+                allowed.push("image");
+                validate_element(accessory, &format!("{}.accessory", path), &allowed)?;
+            }
+        }
+        "header" => {
+            validate_text(&block["text"], &format!("{}.text", path), MAX_HEADER_TEXT, true)?;
+        }
+        "context" => {
+            let elements = array_field(block, "elements", path)?;
+            if elements.is_empty() || elements.len() > MAX_CONTEXT_ELEMENTS {
+                return fail(
+                    &format!("{}.elements", path),
+                    format!("context needs 1 to {} elements", MAX_CONTEXT_ELEMENTS),
+                );
+            }
+            for (i, element) in elements.iter().enumerate() {
+                validate_element(
+                    element,
+                    &format!("{}.elements[{}]", path, i),
+                    &["image", "plain_text", "mrkdwn"],
+                )?;
+            }
+        }
+        "actions" => {
+            let elements = array_field(block, "elements", path)?;
+            if elements.is_empty() || elements.len() > MAX_ACTIONS_ELEMENTS {
+                return fail(
+                    &format!("{}.elements", path),
+                    format!("actions needs 1 to {} elements", MAX_ACTIONS_ELEMENTS),
+                );
+            }
+            for (i, element) in elements.iter().enumerate() {
+                validate_element(
+                    element,
+                    &format!("{}.elements[{}]", path, i),
+                    INTERACTIVE_ELEMENTS,
+                )?;
+            }
+        }
+        "image" => validate_element(block, path, &["image"])?,
+        "input" => {
+            validate_text(&block["label"], &format!("{}.label", path), MAX_FIELD_TEXT, true)?;
+            let mut allowed = INTERACTIVE_ELEMENTS.to_vec();
+            allowed.extend_from_slice(INPUT_ELEMENTS);
+            validate_element(&block["element"], &format!("{}.element", path), &allowed)?;
+        }
+        "rich_text" => {
+            array_field(block, "elements", path)?;
+        }
+        "markdown" => {
+            let text = str_field(block, "text", path)?;
+            check_len(text, MAX_MARKDOWN_TEXT, &format!("{}.text", path), "markdown text")?;
+        }
+        "divider" | "file" | "video" => {}
+        other => {
+            return fail(
+                &format!("{}.type", path),
+                format!("unknown block type `{}`; expected one of {}", other, BLOCK_TYPES.join(", ")),
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Collect every `text` string under `v`, depth first.
+fn collect_text(v: &Value, out: &mut Vec<String>) {
+    match v {
+        Value::Object(map) => {
+            if let Some(Value::String(s)) = map.get("text") {
+                out.push(s.clone());
+            }
+            for (key, child) in map {
+                // Buttons and selects are controls, not content.
+                if key == "accessory" || (key == "elements" && map.get("type") == Some(&json!("actions"))) {
+                    continue;
+                }
+                if key != "text" || child.is_object() {
+                    collect_text(child, out);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|i| collect_text(i, out)),
+        _ => {}
+    }
+}
+
+/// Build the plain `text` Slack shows in notifications and on clients that
+/// cannot render blocks, from the blocks' own text.
+pub fn fallback_text(blocks: &[Value]) -> String {
+    let mut lines = Vec::new();
+    for block in blocks {
+        let mut parts = Vec::new();
+        collect_text(block, &mut parts);
+        let line = parts.join(" ");
+        if !line.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+    let text = lines.join("\n");
+    if text.chars().count() <= MAX_FALLBACK_TEXT {
+        return text;
+    }
+    let mut cut: String = text.chars().take(MAX_FALLBACK_TEXT - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// Fallback text for attachment-only messages: each attachment's `fallback`,
+/// else its `text` or `pretext`, else its blocks.
+pub fn attachments_fallback(attachments: &[Value]) -> String {
+    attachments
+        .iter()
+        .filter_map(|a| {
+            ["fallback", "text", "pretext"]
+                .iter()
+                .find_map(|k| a[*k].as_str().filter(|s| !s.is_empty()).map(String::from))
+                .or_else(|| a["blocks"].as_array().map(|b| fallback_text(b)))
+        })
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_common_blocks() {
+        let blocks = json!([
+            { "type": "header", "text": { "type": "plain_text", "text": "Deploy" } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": "*prod* is green" },
+              "accessory": { "type": "button", "text": { "type": "plain_text", "text": "Open" }, "url": "https://x" } },
+            { "type": "divider" },
+            { "type": "context", "elements": [{ "type": "mrkdwn", "text": "by ci" }] },
+            { "type": "actions", "elements": [
+                { "type": "button", "style": "primary", "text": { "type": "plain_text", "text": "OK" } },
+            ] },
+        ]);
+        validate_blocks(blocks.as_array().unwrap(), "blocks").unwrap();
+        assert_eq!(fallback_text(blocks.as_array().unwrap()), "Deploy\n*prod* is green\nby ci");
+    }
+
+    #[test]
+    fn errors_point_at_the_block() {
+        let long = "x".repeat(MAX_SECTION_TEXT + 1);
+        let cases = [
+            (json!([{ "type": "divider" }, { "type": "section", "text": { "type": "mrkdwn", "text": long } }]),
+             "blocks[1].text.text"),
+            (json!([{ "type": "carousel" }]), "blocks[0].type"),
+            (json!([{ "type": "actions", "elements": [{ "type": "plain_text_input" }] }]),
+             "blocks[0].elements[0].type"),
+            (json!([{ "type": "header", "text": { "type": "mrkdwn", "text": "hi" } }]),
+             "blocks[0].text.type"),
+            (json!([{ "type": "section" }]), "blocks[0]"),
+        ];
+        for (blocks, path) in cases {
+            let err = validate_blocks(blocks.as_array().unwrap(), "blocks").unwrap_err();
+            assert_eq!(err.path, path, "{}", err);
+        }
+
+        let too_many = vec![json!({ "type": "divider" }); MAX_BLOCKS + 1];
+        assert_eq!(validate_blocks(&too_many, "blocks").unwrap_err().path, "blocks");
+    }
+
+    #[test]
+    fn attachments_blocks_and_string_input() {
+        let attachments = parse_array(
+            json!(r#"[{"color": "good", "blocks": [{"type": "section"}]}]"#),
+            "attachments",
+        )
+        .unwrap();
+        let err = validate_attachments(&attachments, "attachments").unwrap_err();
+        assert_eq!(err.path, "attachments[0].blocks[0]");
+
+        assert!(parse_array(json!("not json"), "blocks").is_err());
+        assert_eq!(
+            attachments_fallback(&[json!({ "fallback": "build failed" })]),
+            "build failed"
+        );
+    }
+}
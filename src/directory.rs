@@ -0,0 +1,525 @@
+//! Lazily-filled cache of user and channel names, used to make message output
+//! readable without extra `get_users` round trips.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::error::SlackError;
+use crate::slack_client::{PageLimits, SlackClient, TokenKind};
+
+/// How long a looked-up name is trusted before it is fetched again.
+const DIRECTORY_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Names missing from the last full listing are not looked up again until
+/// it is this old, so typos do not re-page conversations.list or users.list.
+const RELIST_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Entry {
+    name: String,
+    fetched: Instant,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl Cache {
+    async fn get(&self, id: &str) -> Option<String> {
+        let entries = self.entries.lock().await;
+        entries
+            .get(id)
+            .filter(|e| e.fetched.elapsed() < DIRECTORY_TTL)
+            .map(|e| e.name.clone())
+    }
+
+    async fn insert(&self, id: &str, name: String) {
+        self.entries.lock().await.insert(
+            id.to_string(),
+            Entry {
+                name,
+                fetched: Instant::now(),
+            },
+        );
+    }
+}
+
+/// Lowercased name → every ID going by it.
+type NameIndex = HashMap<String, Vec<String>>;
+
+/// The last full listing, and when it was fetched.
+type Listing = Mutex<Option<(Instant, NameIndex)>>;
+
+fn is_fresh(listing: &Option<(Instant, NameIndex)>) -> bool {
+    listing
+        .as_ref()
+        .is_some_and(|(fetched, _)| fetched.elapsed() < RELIST_INTERVAL)
+}
+
+#[derive(Default)]
+pub struct Directory {
+    /// User ID → display name.
+    users: Cache,
+    /// Channel ID → channel name.
+    channels: Cache,
+    /// Lowercased channel name, or `@<token kind>:<user IDs>` for DMs → channel ID.
+    channel_ids: Cache,
+    /// Lowercased user handle / display name / real name → user ID.
+    user_ids: Cache,
+    channel_listing: Listing,
+    user_listing: Listing,
+}
+
+/// Slack IDs are a type letter followed by uppercase letters and digits.
+/// Requiring a digit keeps an all-caps name like `#DEV` from passing as an ID.
+pub(crate) fn is_slack_id(s: &str, prefixes: &[char]) -> bool {
+    s.len() > 1
+        && s.starts_with(prefixes)
+        && s.chars().any(|c| c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Conversation IDs: C (public), G (private/legacy group), D (DM).
+fn is_channel_id(s: &str) -> bool {
+    is_slack_id(s, &['C', 'G', 'D'])
+}
+
+fn is_user_id(s: &str) -> bool {
+    is_slack_id(s, &['U', 'W'])
+}
+
+/// Pick the single ID for `name` out of `index`, or explain why not.
+fn unique(
+    index: &NameIndex,
+    name: &str,
+    not_found: SlackError,
+) -> Result<String, SlackError> {
+    match index.get(name).map(Vec::as_slice) {
+        Some([id]) => Ok(id.clone()),
+        Some(ids) if !ids.is_empty() => Err(SlackError::AmbiguousName {
+            name: name.to_string(),
+            candidates: ids.to_vec(),
+        }),
+        _ => Err(not_found),
+    }
+}
+
+impl Directory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Display name for a user ID (display name, then real name, then handle).
+    /// `None` if Slack could not tell us.
+    pub async fn user_name(&self, client: &SlackClient, id: &str) -> Option<String> {
+        if let Some(name) = self.users.get(id).await {
+            return Some(name);
+        }
+        let data = client
+            .call("users.info", json!({ "user": id }))
+            .await
+            .ok()?;
+        let user = &data["user"];
+        let name = [
+            user.pointer("/profile/display_name"),
+            user.pointer("/profile/real_name"),
+            user.get("real_name"),
+            user.get("name"),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .find(|s| !s.is_empty())?
+        .to_string();
+        self.users.insert(id, name.clone()).await;
+        Some(name)
+    }
+
+    pub async fn channel_name(&self, client: &SlackClient, id: &str) -> Option<String> {
+        if let Some(name) = self.channels.get(id).await {
+            return Some(name);
+        }
+        let data = client
+            .call("conversations.info", json!({ "channel": id }))
+            .await
+            .ok()?;
+        let name = data["channel"]["name"]
+            .as_str()
+            .filter(|s| !s.is_empty())?
+            .to_string();
+        self.channels.insert(id, name.clone()).await;
+        Some(name)
+    }
+
+    /// Turn a channel reference into a conversation ID. Accepts IDs, `#name`,
+    /// bare names and `@user` (user ID or name; opens a DM as `kind`).
+    pub async fn resolve_channel(
+        &self,
+        client: &SlackClient,
+        kind: Option<TokenKind>,
+        input: &str,
+    ) -> Result<String, SlackError> {
+        let input = input.trim();
+        if let Some(user) = input.strip_prefix('@') {
+            return self.open_dm(client, kind, &[user]).await;
+        }
+        let name = input.strip_prefix('#').unwrap_or(input);
+        if is_channel_id(name) {
+            return Ok(name.to_string());
+        }
+
+        let key = name.to_lowercase();
+        if let Some(id) = self.channel_ids.get(&key).await {
+            return Ok(id);
+        }
+
+        // Held across the fetch so concurrent misses share one listing.
+        let mut listing = self.channel_listing.lock().await;
+        if !is_fresh(&listing) {
+            let page = client
+                .paginate(
+                    "conversations.list",
+                    json!({
+                        "types": "public_channel,private_channel",
+                        "exclude_archived": true,
+                        "limit": 1000,
+                    }),
+                    "channels",
+                    PageLimits::default(),
+                )
+                .await?;
+
+            let mut index = NameIndex::new();
+            for c in &page.items {
+                if let (Some(id), Some(name)) = (c["id"].as_str(), c["name"].as_str()) {
+                    self.channels.insert(id, name.to_string()).await;
+                    index
+                        .entry(name.to_lowercase())
+                        .or_default()
+                        .push(id.to_string());
+                }
+            }
+            for (name, ids) in &index {
+                if let [id] = ids.as_slice() {
+                    self.channel_ids.insert(name, id.clone()).await;
+                }
+            }
+            *listing = Some((Instant::now(), index));
+        }
+
+        unique(
+            &listing.as_ref().expect("listed above").1,
+            &key,
+            SlackError::ChannelNotFound {
+                method: "conversations.list".to_string(),
+                channel: Some(input.to_string()),
+            },
+        )
+    }
+
+    /// Open (or reuse) the DM with `users`, each given as a user ID or a name
+    /// (optionally `@`-prefixed). Several users open a group DM. The DM is
+    /// opened as `kind` (the client's default when `None`): the bot's DM with
+    /// someone is a different conversation from the user token's.
+    pub async fn open_dm<S: AsRef<str>>(
+        &self,
+        client: &SlackClient,
+        kind: Option<TokenKind>,
+        users: &[S],
+    ) -> Result<String, SlackError> {
+        let mut user_ids = Vec::with_capacity(users.len());
+        for user in users {
+            let user = user.as_ref().trim();
+            let user = user.strip_prefix('@').unwrap_or(user);
+            user_ids.push(self.resolve_user(client, user).await?);
+        }
+        user_ids.sort();
+        user_ids.dedup();
+        let users = user_ids.join(",");
+        let kind = kind.unwrap_or(if client.has_token(TokenKind::Bot) {
+            TokenKind::Bot
+        } else {
+            TokenKind::User
+        });
+        let key = format!("@{}:{}", kind, users);
+        if let Some(id) = self.channel_ids.get(&key).await {
+            return Ok(id);
+        }
+
+        let data = client
+            .post_with(Some(kind), "conversations.open", json!({ "users": users }))
+            .await?;
+        let id = data["channel"]["id"]
+            .as_str()
+            .ok_or_else(|| SlackError::Api {
+                method: "conversations.open".to_string(),
+                error: "missing channel id in response".to_string(),
+            })?
+            .to_string();
+        self.channel_ids.insert(&key, id.clone()).await;
+        Ok(id)
+    }
+
+    /// Turn a user ID, handle, display name or real name into a user ID.
+    pub async fn resolve_user(
+        &self,
+        client: &SlackClient,
+        user: &str,
+    ) -> Result<String, SlackError> {
+        if is_user_id(user) {
+            return Ok(user.to_string());
+        }
+        let key = user.to_lowercase();
+        if let Some(id) = self.user_ids.get(&key).await {
+            return Ok(id);
+        }
+
+        let mut listing = self.user_listing.lock().await;
+        if !is_fresh(&listing) {
+            let page = client
+                .paginate(
+                    "users.list",
+                    json!({ "limit": 1000 }),
+                    "members",
+                    PageLimits::default(),
+                )
+                .await?;
+
+            let mut index = NameIndex::new();
+            for u in page.items.iter().filter(|u| u["deleted"] != json!(true)) {
+                let Some(id) = u["id"].as_str() else {
+                    continue;
+                };
+                let names: HashSet<String> = [
+                    u.get("name"),
+                    u.pointer("/profile/display_name"),
+                    u.pointer("/profile/real_name"),
+                    u.get("real_name"),
+                ]
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(str::to_lowercase)
+                .collect();
+                for name in names {
+                    index.entry(name).or_default().push(id.to_string());
+                }
+            }
+            for (name, ids) in &index {
+                if let [id] = ids.as_slice() {
+                    self.user_ids.insert(name, id.clone()).await;
+                }
+            }
+            *listing = Some((Instant::now(), index));
+        }
+
+        unique(
+            &listing.as_ref().expect("listed above").1,
+            &key,
+            SlackError::UserNotFound {
+                user: user.to_string(),
+            },
+        )
+    }
+
+    /// Add `user_name` to each message and rewrite `<@U…>` / `<#C…>` mentions
+    /// in its text to `@name` / `#name`. IDs that cannot be resolved are left as-is.
+    pub async fn resolve_messages(&self, client: &SlackClient, messages: &mut Value) {
+        let Some(messages) = messages.as_array_mut() else {
+            return;
+        };
+
+        let mut user_ids = HashSet::new();
+        let mut channel_ids = HashSet::new();
+        for m in messages.iter() {
+            if let Some(user) = m["user"].as_str() {
+                user_ids.insert(user.to_string());
+            }
+            for mention in mentions(m["text"].as_str().unwrap_or_default()) {
+                match mention {
+                    Mention::User(id) => user_ids.insert(id),
+                    Mention::Channel(id) => channel_ids.insert(id),
+                };
+            }
+        }
+
+        let mut users = HashMap::new();
+        for id in user_ids {
+            if let Some(name) = self.user_name(client, &id).await {
+                users.insert(id, name);
+            }
+        }
+        let mut channels = HashMap::new();
+        for id in channel_ids {
+            if let Some(name) = self.channel_name(client, &id).await {
+                channels.insert(id, name);
+            }
+        }
+
+        for m in messages.iter_mut() {
+            if let Some(name) = m["user"].as_str().and_then(|u| users.get(u)) {
+                m["user_name"] = json!(format!("@{}", name));
+            }
+            if let Some(text) = m["text"].as_str() {
+                m["text"] = json!(rewrite_mentions(text, &users, &channels));
+            }
+        }
+    }
+}
+
+enum Mention {
+    User(String),
+    Channel(String),
+}
+
+/// Split the inside of a `<...>` token into (sigil, id). Handles both `<@U1>`
+/// and the labelled `<#C1|general>` forms.
+fn parse_token(inner: &str) -> Option<(char, &str)> {
+    let sigil = inner.chars().next()?;
+    if sigil != '@' && sigil != '#' {
+        return None;
+    }
+    let id = inner[1..].split('|').next()?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some((sigil, id))
+}
+
+fn mentions(text: &str) -> Vec<Mention> {
+    let mut out = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        match parse_token(&rest[start + 1..start + len]) {
+            Some(('@', id)) => out.push(Mention::User(id.to_string())),
+            Some((_, id)) => out.push(Mention::Channel(id.to_string())),
+            None => {}
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out
+}
+
+fn rewrite_mentions(
+    text: &str,
+    users: &HashMap<String, String>,
+    channels: &HashMap<String, String>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let Some(len) = rest[start..].find('>') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let token = &rest[start..start + len + 1];
+        let name = match parse_token(&token[1..token.len() - 1]) {
+            Some(('@', id)) => users.get(id).map(|n| format!("@{}", n)),
+            Some((_, id)) => channels.get(id).map(|n| format!("#{}", n)),
+            None => None,
+        };
+        out.push_str(name.as_deref().unwrap_or(token));
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::MockSlack;
+
+    #[tokio::test]
+    async fn resolves_names_ids_and_dms() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general")
+            .add_channel("C002", "random")
+            .add_user("U001", "alice");
+        let client = mock.client();
+        let directory = Directory::new();
+
+        for input in ["C001", "#general", "general", "#General"] {
+            assert_eq!(
+                directory.resolve_channel(&client, None, input).await.unwrap(),
+                "C001"
+            );
+        }
+        assert_eq!(mock.calls("conversations.list").len(), 1);
+
+        let dm = directory.resolve_channel(&client, None, "@alice").await.unwrap();
+        assert!(dm.starts_with('D'));
+        assert_eq!(
+            directory.resolve_channel(&client, None, "@U001").await.unwrap(),
+            dm
+        );
+        assert_eq!(mock.calls("conversations.open").len(), 1);
+
+        // The user token's DM is opened (and cached) separately.
+        directory
+            .open_dm(&client, Some(TokenKind::User), &["alice"])
+            .await
+            .unwrap();
+        directory
+            .open_dm(&client, Some(TokenKind::User), &["U001"])
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.tokens("conversations.open"),
+            [crate::mock_slack::TEST_TOKEN, crate::mock_slack::TEST_USER_TOKEN]
+        );
+    }
+
+    #[tokio::test]
+    async fn ambiguous_and_unknown_names() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "ops").add_channel("C002", "ops");
+        let client = mock.client();
+        let directory = Directory::new();
+
+        match directory.resolve_channel(&client, None, "#ops").await {
+            Err(SlackError::AmbiguousName { candidates, .. }) => {
+                assert_eq!(candidates, ["C001", "C002"])
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(matches!(
+            directory.resolve_channel(&client, None, "#nope").await,
+            Err(SlackError::ChannelNotFound { .. })
+        ));
+        assert!(matches!(
+            directory.resolve_channel(&client, None, "@nobody").await,
+            Err(SlackError::UserNotFound { .. })
+        ));
+
+        // Misses are answered from the last listing, not a fresh one.
+        assert!(directory.resolve_channel(&client, None, "#nope").await.is_err());
+        assert!(directory.resolve_channel(&client, None, "@nobody").await.is_err());
+        assert_eq!(mock.calls("conversations.list").len(), 1);
+        assert_eq!(mock.calls("users.list").len(), 1);
+    }
+
+    #[test]
+    fn rewrites_known_mentions_only() {
+        let users = HashMap::from([("U1".to_string(), "alice".to_string())]);
+        let channels = HashMap::from([("C1".to_string(), "general".to_string())]);
+
+        assert_eq!(
+            rewrite_mentions(
+                "hey <@U1>, see <#C1|general> and <@U2> <https://x.y>",
+                &users,
+                &channels
+            ),
+            "hey @alice, see #general and <@U2> <https://x.y>"
+        );
+    }
+}
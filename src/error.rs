@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+
+use rmcp::model::{ErrorCode, ErrorData};
+use serde_json::{json, Value};
+
+use crate::slack_client::TokenKind;
+
+// MCP error codes for Slack failures, in the JSON-RPC server-error range.
+// Kept clear of -32002, which MCP uses for "resource not found".
+pub const RATE_LIMITED: ErrorCode = ErrorCode(-32010);
+pub const NOT_IN_CHANNEL: ErrorCode = ErrorCode(-32011);
+pub const CHANNEL_NOT_FOUND: ErrorCode = ErrorCode(-32012);
+pub const MISSING_SCOPE: ErrorCode = ErrorCode(-32013);
+pub const INVALID_AUTH: ErrorCode = ErrorCode(-32014);
+pub const AMBIGUOUS_NAME: ErrorCode = ErrorCode(-32015);
+pub const USER_NOT_FOUND: ErrorCode = ErrorCode(-32016);
+pub const MISSING_TOKEN: ErrorCode = ErrorCode(-32017);
+pub const NO_PERMISSION: ErrorCode = ErrorCode(-32018);
+pub const SLACK_API: ErrorCode = ErrorCode(-32020);
+pub const HTTP: ErrorCode = ErrorCode(-32021);
+pub const TRANSPORT: ErrorCode = ErrorCode(-32022);
+
+#[derive(Debug, thiserror::Error)]
+pub enum SlackError {
+    #[error("Slack API rate limited in {method} (Retry-After {retry_after}s)")]
+    RateLimited { method: String, retry_after: u64 },
+
+    #[error("Slack API error in {method}: not_in_channel")]
+    NotInChannel {
+        method: String,
+        channel: Option<String>,
+    },
+
+    #[error("Slack API error in {method}: channel_not_found")]
+    ChannelNotFound {
+        method: String,
+        channel: Option<String>,
+    },
+
+    #[error("Slack API error in {method}: missing_scope (needed: {})", needed.join(", "))]
+    MissingScope {
+        method: String,
+        needed: Vec<String>,
+        provided: Vec<String>,
+    },
+
+    #[error("Slack API error in {method}: {error}")]
+    InvalidAuth { method: String, error: String },
+
+    #[error("Slack API error in {method}: no_permission")]
+    NoPermission { method: String },
+
+    #[error("'{name}' is ambiguous; it matches {}", candidates.join(", "))]
+    AmbiguousName {
+        name: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("No Slack user matches '{user}'")]
+    UserNotFound { user: String },
+
+    #[error("{method} needs a Slack {kind} token, but {} is not set", kind.env_var())]
+    MissingToken { method: String, kind: TokenKind },
+
+    #[error("Slack API error in {method}: {error}")]
+    Api { method: String, error: String },
+
+    #[error("Slack API HTTP error {status} in {method}: {body}")]
+    Http {
+        method: String,
+        status: u16,
+        body: String,
+    },
+
+    #[error("Slack API request failed in {method}: {source}")]
+    Transport {
+        method: String,
+        #[source]
+        source: reqwest::Error,
+    },
+}
+
+fn scopes(data: &Value, key: &str) -> Vec<String> {
+    data.get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| {
+            s.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl SlackError {
+    /// Classify an `ok: false` Slack response. `body` is the request body, used
+    /// to report which channel a channel error refers to.
+    pub fn from_response(method: &str, body: &Value, data: &Value) -> Self {
+        let method = method.to_string();
+        let error = data
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let channel = body
+            .get("channel")
+            .or_else(|| body.get("channel_id"))
+            .and_then(|c| c.as_str())
+            .map(String::from);
+
+        match error.as_str() {
+            "ratelimited" => SlackError::RateLimited {
+                method,
+                retry_after: 0,
+            },
+            "not_in_channel" => SlackError::NotInChannel { method, channel },
+            "channel_not_found" => SlackError::ChannelNotFound { method, channel },
+            "missing_scope" => SlackError::MissingScope {
+                method,
+                needed: scopes(data, "needed"),
+                provided: scopes(data, "provided"),
+            },
+            "not_authed" | "invalid_auth" | "account_inactive" | "token_revoked"
+            | "token_expired" => SlackError::InvalidAuth { method, error },
+            "no_permission" => SlackError::NoPermission { method },
+            _ => SlackError::Api { method, error },
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SlackError::RateLimited { .. } => RATE_LIMITED,
+            SlackError::NotInChannel { .. } => NOT_IN_CHANNEL,
+            SlackError::ChannelNotFound { .. } => CHANNEL_NOT_FOUND,
+            SlackError::MissingScope { .. } => MISSING_SCOPE,
+            SlackError::InvalidAuth { .. } => INVALID_AUTH,
+            SlackError::NoPermission { .. } => NO_PERMISSION,
+            SlackError::AmbiguousName { .. } => AMBIGUOUS_NAME,
+            SlackError::UserNotFound { .. } => USER_NOT_FOUND,
+            SlackError::MissingToken { .. } => MISSING_TOKEN,
+            SlackError::Api { .. } => SLACK_API,
+            SlackError::Http { .. } => HTTP,
+            SlackError::Transport { .. } => TRANSPORT,
+        }
+    }
+
+    /// Machine-readable details for the MCP error `data` field, including a
+    /// hint the calling agent can act on.
+    pub fn data(&self) -> Value {
+        match self {
+            SlackError::RateLimited {
+                method,
+                retry_after,
+            } => json!({
+                "slack_error": "ratelimited",
+                "method": method,
+                "retry_after": retry_after,
+                "hint": "Slack is rate limiting this method. Wait retry_after seconds, then retry.",
+            }),
+            SlackError::NotInChannel { method, channel } => json!({
+                "slack_error": "not_in_channel",
+                "method": method,
+                "channel": channel,
+                "hint": "The app is not a member of this channel. Call join_channel with this \
+                         channel (public channels only) or ask a member to invite the app, then retry.",
+            }),
+            SlackError::ChannelNotFound { method, channel } => json!({
+                "slack_error": "channel_not_found",
+                "method": method,
+                "channel": channel,
+                "hint": "No channel with this ID or name is visible to the app. Use list_channels \
+                         to find the right channel; private channels require the app to be invited.",
+            }),
+            SlackError::MissingScope {
+                method,
+                needed,
+                provided,
+            } => json!({
+                "slack_error": "missing_scope",
+                "method": method,
+                "needed": needed,
+                "provided": provided,
+                "hint": "The Slack token lacks the scopes listed in `needed`. Add them to the \
+                         Slack app and reinstall it; retrying will not help.",
+            }),
+            SlackError::InvalidAuth { method, error } => json!({
+                "slack_error": error,
+                "method": method,
+                "hint": "The bot or user token in use is missing, invalid or revoked. Check \
+                         SLACK_BOT_TOKEN and SLACK_USER_TOKEN, or the session's own Slack token; \
+                         retrying will not help.",
+            }),
+            SlackError::NoPermission { method } => json!({
+                "slack_error": "no_permission",
+                "method": method,
+                "hint": "The token is valid, but the workspace or the object does not allow it \
+                         this action. Ask an owner or admin for access, or retry with a different \
+                         as_user setting where the tool offers one.",
+            }),
+            SlackError::AmbiguousName { name, candidates } => json!({
+                "name": name,
+                "candidates": candidates,
+                "hint": "Several conversations or users share this name. Retry with one of the \
+                         IDs in `candidates`.",
+            }),
+            SlackError::UserNotFound { user } => json!({
+                "user": user,
+                "hint": "Use get_users to find the user's ID, then retry with @<ID>.",
+            }),
+            SlackError::MissingToken { method, kind } => json!({
+                "method": method,
+                "token_type": kind.to_string(),
+                "hint": format!(
+                    "This call needs a {} token. Set {} on the server, or retry with a different \
+                     as_user setting where the tool offers one.",
+                    kind,
+                    kind.env_var()
+                ),
+            }),
+            SlackError::Api { method, error } => json!({
+                "slack_error": error,
+                "method": method,
+            }),
+            SlackError::Http {
+                method,
+                status,
+                body,
+            } => json!({
+                "method": method,
+                "status": status,
+                "body": body,
+            }),
+            SlackError::Transport { method, source } => json!({
+                "method": method,
+                "timeout": source.is_timeout(),
+                "hint": "Could not reach Slack. Retrying later may help.",
+            }),
+        }
+    }
+}
+
+impl From<SlackError> for ErrorData {
+    fn from(e: SlackError) -> Self {
+        ErrorData {
+            code: e.code(),
+            message: Cow::from(e.to_string()),
+            data: Some(e.data()),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! In-process fan-out of incoming Slack events (from Socket Mode or the
+//! Events API endpoint) to the features waiting on them.
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+/// Events kept for slow receivers before they start missing some.
+const BUS_CAPACITY: usize = 1024;
+
+/// Slack timestamps ("1700000000.000123") as comparable numbers.
+pub fn ts_key(ts: &str) -> (u64, u64) {
+    let (secs, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+    (secs.parse().unwrap_or(0), micros.parse().unwrap_or(0))
+}
+
+/// An event from the Events API, as found in a callback's `event` field, or
+/// an interaction payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlackEvent {
+    /// A `message` event: new messages and thread replies, edits and deletes.
+    Message(Value),
+    /// `reaction_added` or `reaction_removed`.
+    Reaction(Value),
+    /// `app_mention`.
+    AppMention(Value),
+    /// A `block_actions` interaction: someone clicked a button in a message.
+    Interaction(Value),
+}
+
+impl SlackEvent {
+    /// Classify an Events API `event` object. Types nothing here consumes
+    /// are dropped.
+    pub fn from_event(event: &Value) -> Option<Self> {
+        match event["type"].as_str()? {
+            "message" => Some(SlackEvent::Message(event.clone())),
+            "reaction_added" | "reaction_removed" => Some(SlackEvent::Reaction(event.clone())),
+            "app_mention" => Some(SlackEvent::AppMention(event.clone())),
+            _ => None,
+        }
+    }
+
+    /// Classify an interaction payload. Only `block_actions` is consumed.
+    pub fn from_interaction(payload: &Value) -> Option<Self> {
+        (payload["type"] == "block_actions").then(|| SlackEvent::Interaction(payload.clone()))
+    }
+
+    pub fn payload(&self) -> &Value {
+        match self {
+            SlackEvent::Message(v)
+            | SlackEvent::Reaction(v)
+            | SlackEvent::AppMention(v)
+            | SlackEvent::Interaction(v) => v,
+        }
+    }
+
+    /// The conversation the event happened in.
+    pub fn channel(&self) -> Option<&str> {
+        match self {
+            SlackEvent::Reaction(v) => v["item"]["channel"].as_str(),
+            SlackEvent::Message(v) | SlackEvent::AppMention(v) => v["channel"].as_str(),
+            SlackEvent::Interaction(v) => v["channel"]["id"].as_str(),
+        }
+    }
+
+    /// ts of the message itself (for reactions and interactions, of the
+    /// message reacted to or clicked in).
+    pub fn ts(&self) -> Option<&str> {
+        match self {
+            SlackEvent::Reaction(v) => v["item"]["ts"].as_str(),
+            SlackEvent::Interaction(v) => v["container"]["message_ts"].as_str(),
+            SlackEvent::Message(v) | SlackEvent::AppMention(v) => v["ts"].as_str(),
+        }
+    }
+
+    /// The thread a message belongs to, if it is a reply (or a thread parent).
+    pub fn thread_ts(&self) -> Option<&str> {
+        match self {
+            SlackEvent::Reaction(_) | SlackEvent::Interaction(_) => None,
+            SlackEvent::Message(v) | SlackEvent::AppMention(v) => v["thread_ts"].as_str(),
+        }
+    }
+}
+
+/// Broadcast channel for `SlackEvent`s. Cloning shares the same bus.
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<SlackEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(BUS_CAPACITY);
+        Self { tx }
+    }
+
+    /// Hand `event` to every current receiver. Nobody listening is fine.
+    pub fn publish(&self, event: SlackEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Receive events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<SlackEvent> {
+        self.tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn classifies_and_fans_out_events() {
+        let bus = EventBus::new();
+        let mut a = bus.subscribe();
+        let mut b = bus.subscribe();
+
+        let reply = json!({
+            "type": "message", "channel": "C001", "ts": "2.0", "thread_ts": "1.0", "text": "hi",
+        });
+        let reaction = json!({
+            "type": "reaction_added", "reaction": "+1",
+            "item": { "type": "message", "channel": "C001", "ts": "1.0" },
+        });
+        assert_eq!(SlackEvent::from_event(&json!({ "type": "team_join" })), None);
+
+        for event in [&reply, &reaction] {
+            bus.publish(SlackEvent::from_event(event).unwrap());
+        }
+        for rx in [&mut a, &mut b] {
+            let first = rx.recv().await.unwrap();
+            assert_eq!(first.thread_ts(), Some("1.0"));
+            let second = rx.recv().await.unwrap();
+            assert!(matches!(second, SlackEvent::Reaction(_)));
+            assert_eq!((second.channel(), second.ts()), (Some("C001"), Some("1.0")));
+        }
+    }
+}
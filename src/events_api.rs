@@ -0,0 +1,253 @@
+//! Slack Events API and interactivity receivers: HTTP endpoints Slack posts
+//! events and button clicks to, for deployments where Socket Mode is not
+//! allowed. Mounted on the MCP HTTP listener.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::events::{EventBus, SlackEvent};
+
+/// Where Slack's Event Subscriptions request URL points unless configured.
+pub const DEFAULT_PATH: &str = "/slack/events";
+
+/// Where Slack's Interactivity request URL points unless configured.
+pub const DEFAULT_INTERACTIVITY_PATH: &str = "/slack/interactivity";
+
+/// Requests signed longer ago than this are treated as replays.
+const MAX_AGE_SECS: i64 = 5 * 60;
+
+/// How long an event_id is remembered. Slack retries an unacknowledged
+/// event three times, the last about five minutes after the first.
+const SEEN_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone)]
+struct Receiver {
+    signing_secret: Arc<String>,
+    bus: EventBus,
+    /// event_ids already published, with when they arrived.
+    seen: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl Receiver {
+    /// Whether `event_id` is new, remembering it if so. Retries of an event
+    /// that was handled (X-Slack-Retry-Num) must not be published again.
+    fn first_delivery(&self, event_id: &str) -> bool {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, at| now.duration_since(*at) < SEEN_TTL);
+        seen.insert(event_id.to_string(), now).is_none()
+    }
+}
+
+/// Routes for the Events API endpoint at `path` and the interactivity
+/// endpoint at `interactivity_path`, to merge into the MCP HTTP router.
+pub fn router(path: &str, interactivity_path: &str, signing_secret: String, bus: EventBus) -> Router {
+    Router::new()
+        .route(path, post(receive))
+        .route(interactivity_path, post(interact))
+        .with_state(Receiver {
+            signing_secret: Arc::new(signing_secret),
+            bus,
+            seen: Arc::default(),
+        })
+}
+
+/// Check `X-Slack-Signature` and `X-Slack-Request-Timestamp` against the
+/// raw body. See https://api.slack.com/authentication/verifying-requests-from-slack.
+pub fn verify(
+    signing_secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> Result<(), &'static str> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let timestamp = header("X-Slack-Request-Timestamp").ok_or("missing request timestamp")?;
+    let signature = header("X-Slack-Signature").ok_or("missing signature")?;
+
+    let sent: i64 = timestamp.parse().map_err(|_| "bad request timestamp")?;
+    if (now - sent).abs() > MAX_AGE_SECS {
+        return Err("request timestamp too old");
+    }
+    let signature = signature
+        .strip_prefix("v0=")
+        .and_then(|hex_sig| hex::decode(hex_sig).ok())
+        .ok_or("malformed signature")?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(signing_secret.as_bytes())
+        .map_err(|_| "unusable signing secret")?;
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    // Constant-time comparison.
+    mac.verify_slice(&signature).map_err(|_| "signature mismatch")
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> Response {
+    let now = chrono::Utc::now().timestamp();
+    if let Err(reason) = verify(&receiver.signing_secret, &headers, &body, now) {
+        tracing::warn!("Rejected Events API request: {}", reason);
+        return (StatusCode::UNAUTHORIZED, reason).into_response();
+    }
+    let Ok(payload) = serde_json::from_slice::<Value>(&body) else {
+        return (StatusCode::BAD_REQUEST, "body is not JSON").into_response();
+    };
+
+    match payload["type"].as_str().unwrap_or_default() {
+        "url_verification" => Json(json!({ "challenge": payload["challenge"] })).into_response(),
+        "event_callback" => {
+            if let Some(id) = payload["event_id"].as_str() {
+                if !receiver.first_delivery(id) {
+                    tracing::debug!("Ignoring redelivered event {}", id);
+                    return StatusCode::OK.into_response();
+                }
+            }
+            if let Some(event) = SlackEvent::from_event(&payload["event"]) {
+                receiver.bus.publish(event);
+            }
+            StatusCode::OK.into_response()
+        }
+        other => {
+            tracing::debug!("Ignoring Events API request of type {}", other);
+            StatusCode::OK.into_response()
+        }
+    }
+}
+
+/// Interactions arrive form-encoded, as JSON in a `payload` field.
+async fn interact(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> Response {
+    let now = chrono::Utc::now().timestamp();
+    if let Err(reason) = verify(&receiver.signing_secret, &headers, &body, now) {
+        tracing::warn!("Rejected interactivity request: {}", reason);
+        return (StatusCode::UNAUTHORIZED, reason).into_response();
+    }
+    let payload = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&body)
+        .ok()
+        .and_then(|pairs| pairs.into_iter().find(|(k, _)| k == "payload"))
+        .and_then(|(_, v)| serde_json::from_str::<Value>(&v).ok());
+    let Some(payload) = payload else {
+        return (StatusCode::BAD_REQUEST, "missing payload").into_response();
+    };
+    if let Some(event) = SlackEvent::from_interaction(&payload) {
+        receiver.bus.publish(event);
+    }
+    StatusCode::OK.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+
+    fn sign(body: &str, timestamp: i64) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:{}", timestamp, body).as_bytes());
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    fn headers(signature: &str, timestamp: i64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Slack-Signature", signature.parse().unwrap());
+        headers.insert("X-Slack-Request-Timestamp", timestamp.into());
+        headers
+    }
+
+    #[test]
+    fn verifies_signatures_and_rejects_replays() {
+        let body = r#"{"type":"event_callback"}"#;
+        let now = 1_700_000_000;
+        let good = headers(&sign(body, now), now);
+        assert_eq!(verify(SECRET, &good, body.as_bytes(), now + 10), Ok(()));
+
+        assert_eq!(
+            verify(SECRET, &good, b"{\"type\":\"tampered\"}", now),
+            Err("signature mismatch")
+        );
+        assert_eq!(
+            verify("another-secret", &good, body.as_bytes(), now),
+            Err("signature mismatch")
+        );
+        let old = now - MAX_AGE_SECS - 1;
+        assert_eq!(
+            verify(SECRET, &headers(&sign(body, old), old), body.as_bytes(), now),
+            Err("request timestamp too old")
+        );
+        assert_eq!(
+            verify(SECRET, &HeaderMap::new(), body.as_bytes(), now),
+            Err("missing request timestamp")
+        );
+    }
+
+    #[tokio::test]
+    async fn answers_challenges_and_publishes_events() {
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), DEFAULT_PATH);
+        let app = router(DEFAULT_PATH, DEFAULT_INTERACTIVITY_PATH, SECRET.to_string(), bus);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let http = reqwest::Client::new();
+        let send = |body: Value, signed: bool| {
+            let body = body.to_string();
+            let now = chrono::Utc::now().timestamp();
+            let signature = if signed { sign(&body, now) } else { "v0=00".to_string() };
+            http.post(&url)
+                .header("X-Slack-Signature", signature)
+                .header("X-Slack-Request-Timestamp", now.to_string())
+                .header("Content-Type", "application/json")
+                .body(body)
+                .send()
+        };
+
+        let resp = send(json!({ "type": "url_verification", "challenge": "abc" }), true)
+            .await
+            .unwrap();
+        assert_eq!(resp.json::<Value>().await.unwrap(), json!({ "challenge": "abc" }));
+
+        let event = json!({
+            "type": "event_callback",
+            "event_id": "Ev001",
+            "event": { "type": "message", "channel": "C001", "ts": "1.0", "text": "hi" },
+        });
+        let resp = send(event.clone(), false).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert!(rx.try_recv().is_err());
+
+        let resp = send(event.clone(), true).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        assert_eq!(rx.recv().await.unwrap().channel(), Some("C001"));
+
+        // A retry of the same event is acknowledged but not published twice.
+        let resp = send(event, true).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        assert!(rx.try_recv().is_err());
+
+        let click = json!({ "type": "block_actions", "channel": { "id": "C002" }, "actions": [] });
+        let body = serde_urlencoded::to_string([("payload", click.to_string())]).unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let resp = http
+            .post(url.replace(DEFAULT_PATH, DEFAULT_INTERACTIVITY_PATH))
+            .header("X-Slack-Signature", sign(&body, now))
+            .header("X-Slack-Request-Timestamp", now.to_string())
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+        let event = rx.recv().await.unwrap();
+        assert!(matches!(event, SlackEvent::Interaction(_)));
+        assert_eq!(event.channel(), Some("C002"));
+    }
+}
@@ -0,0 +1,126 @@
+//! Where upload contents come from, and how downloads are handed back.
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+
+/// Largest file `SlackClient::download` will fetch, for download_file and
+/// canvas resources alike.
+pub const MAX_DOWNLOAD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Largest file upload_file will read from disk or accept inline.
+pub const MAX_UPLOAD_BYTES: usize = 50 * 1024 * 1024;
+
+/// Read `path` for upload. It must resolve (after following symlinks) to a
+/// file inside `allowed_dir`; relative paths are taken relative to it.
+/// Returns the file name and contents.
+pub fn read_local(allowed_dir: Option<&Path>, path: &str) -> Result<(String, Vec<u8>), String> {
+    let dir = allowed_dir.ok_or_else(|| {
+        "Uploading local files is disabled. Set SLACK_UPLOAD_DIR on the server to allow files \
+         from that directory."
+            .to_string()
+    })?;
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| format!("SLACK_UPLOAD_DIR {} is not usable: {}", dir.display(), e))?;
+    let requested = PathBuf::from(path);
+    let full = if requested.is_absolute() {
+        requested
+    } else {
+        dir.join(requested)
+    };
+    let full = full
+        .canonicalize()
+        .map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    if !full.starts_with(&dir) {
+        return Err(format!(
+            "{} is outside the upload directory {}",
+            path,
+            dir.display()
+        ));
+    }
+    let meta = std::fs::metadata(&full).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    if !meta.is_file() {
+        return Err(format!("{} is not a file", path));
+    }
+    if meta.len() > MAX_UPLOAD_BYTES as u64 {
+        return Err(format!(
+            "{} is {} bytes; the upload limit is {}",
+            path,
+            meta.len(),
+            MAX_UPLOAD_BYTES
+        ));
+    }
+    let bytes = std::fs::read(&full).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let name = full
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "file".to_string());
+    Ok((name, bytes))
+}
+
+pub fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+    // Tolerate data: URLs and line-wrapped input.
+    let data = data.split_once(";base64,").map_or(data, |(_, d)| d);
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("content_base64 is not valid base64: {}", e))
+}
+
+pub fn encode_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Whether a file with this MIME type should come back as text.
+pub fn is_text_mime(mime: &str) -> bool {
+    let mime = mime.split(';').next().unwrap_or_default().trim();
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/x-yaml"
+                | "application/yaml"
+                | "application/csv"
+        )
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_paths_stay_inside_the_upload_dir() {
+        let root = std::env::temp_dir().join(format!("slack-mcp-upload-{}", std::process::id()));
+        let dir = root.join("allowed");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("report.csv"), "a,b\n1,2\n").unwrap();
+        std::fs::write(root.join("secret.txt"), "nope").unwrap();
+
+        let (name, bytes) = read_local(Some(&dir), "report.csv").unwrap();
+        assert_eq!(name, "report.csv");
+        assert_eq!(bytes, b"a,b\n1,2\n");
+
+        assert!(read_local(Some(&dir), "../secret.txt").unwrap_err().contains("outside"));
+        let absolute = root.join("secret.txt");
+        assert!(read_local(Some(&dir), absolute.to_str().unwrap()).is_err());
+        assert!(read_local(None, "report.csv").unwrap_err().contains("SLACK_UPLOAD_DIR"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn base64_and_mime_helpers() {
+        assert_eq!(decode_base64("data:text/plain;base64,aGk=").unwrap(), b"hi");
+        assert_eq!(decode_base64("aG\nk=").unwrap(), b"hi");
+        assert!(decode_base64("***").is_err());
+
+        assert!(is_text_mime("text/csv; charset=utf-8"));
+        assert!(is_text_mime("application/vnd.api+json"));
+        assert!(!is_text_mime("image/png"));
+    }
+}
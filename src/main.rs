@@ -1,33 +1,222 @@
+mod approval;
+mod auth;
+mod blocks;
+mod directory;
+mod error;
+mod events;
+mod events_api;
+mod files;
+mod markdown;
+#[cfg(test)]
+mod mock_slack;
+mod posted;
+mod rate_limit;
+mod resources;
+mod schedule;
+mod sessions;
+mod shape;
 mod slack_client;
+mod socket_mode;
+mod subscriptions;
 mod tools;
 mod types;
+mod wait;
+
+use std::sync::Arc;
 
 use anyhow::Result;
+use auth::Auth;
+use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+use rmcp::transport::streamable_http_server::session::local::{
+    LocalSessionManager, SessionConfig,
+};
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
 use rmcp::ServiceExt;
+use sessions::Sessions;
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::EnvFilter;
 
-use crate::slack_client::SlackClient;
+use crate::events::EventBus;
+use crate::shape::OutputMode;
+use crate::slack_client::{SlackClient, TokenKind};
+use crate::socket_mode::SocketMode;
 use crate::tools::SlackTools;
 
+/// How long open connections get to finish after ctrl-c before the
+/// streamable HTTP server exits anyway.
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Put the MCP routes behind the session cap and, when it is configured,
+/// bearer-token auth.
+fn guarded(router: axum::Router, sessions: &Sessions, auth: &Option<Arc<Auth>>) -> axum::Router {
+    let router = router.layer(axum::middleware::from_fn_with_state(
+        sessions.clone(),
+        sessions::limit,
+    ));
+    match auth {
+        Some(auth) => router.layer(axum::middleware::from_fn_with_state(
+            auth.clone(),
+            auth::require,
+        )),
+        None => router,
+    }
+}
+
+/// Axum panics on a route without a leading `/` or on one mounted twice,
+/// so refuse such paths up front. Each route is (where it came from, path).
+fn check_routes(routes: &[(&str, &str)]) -> Result<()> {
+    for (i, (name, path)) in routes.iter().enumerate() {
+        if !path.starts_with('/') {
+            anyhow::bail!("{} must start with '/', got '{}'", name, path);
+        }
+        if let Some((other, _)) = routes[..i].iter().find(|(_, p)| p == path) {
+            anyhow::bail!("{} and {} are both '{}'; each needs its own path", other, name, path);
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Tracing MUST go to stderr — stdout is the MCP stdio transport channel.
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
+        .with_env_filter(
+            EnvFilter::from_default_env()
+                .add_directive(tracing::Level::INFO.into())
+                // It logs new connections' headers, bearer and Slack tokens included.
+                .add_directive("rmcp::transport::sse_server=warn".parse()?),
+        )
         .with_writer(std::io::stderr)
         .with_ansi(false)
         .init();
 
     tracing::info!("Starting slack-mcp server");
 
-    let client = SlackClient::from_env()?;
+    let client = Arc::new(SlackClient::from_env()?);
     let default_channel = std::env::var("SLACK_DEFAULT_CHANNEL")
         .ok()
         .filter(|s| !s.is_empty());
 
-    let server = SlackTools::new(client, default_channel);
+    let output_mode = match std::env::var("SLACK_OUTPUT_MODE").ok().filter(|s| !s.is_empty()) {
+        Some(mode) => OutputMode::parse(&mode).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown SLACK_OUTPUT_MODE: '{}'. Use 'compact', 'text' or 'raw'.",
+                mode
+            )
+        })?,
+        None => OutputMode::default(),
+    };
+
+    let allow_unowned_edits = matches!(
+        std::env::var("SLACK_ALLOW_UNOWNED_EDITS").as_deref(),
+        Ok("1") | Ok("true")
+    );
+
+    let upload_dir = std::env::var("SLACK_UPLOAD_DIR")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(std::path::PathBuf::from);
+
+    let poll_interval = match std::env::var("SLACK_POLL_INTERVAL").ok().filter(|s| !s.is_empty()) {
+        Some(secs) => match secs.parse::<u64>() {
+            Ok(secs) if secs > 0 => std::time::Duration::from_secs(secs),
+            _ => anyhow::bail!(
+                "Invalid SLACK_POLL_INTERVAL: '{}'. Use a number of seconds.",
+                secs
+            ),
+        },
+        None => subscriptions::DEFAULT_POLL_INTERVAL,
+    };
+
+    let max_sessions = match std::env::var("MCP_MAX_SESSIONS").ok().filter(|s| !s.is_empty()) {
+        Some(max) => match max.parse::<usize>() {
+            Ok(max) if max > 0 => max,
+            _ => anyhow::bail!("Invalid MCP_MAX_SESSIONS: '{}'. Use a positive number.", max),
+        },
+        None => sessions::DEFAULT_MAX_SESSIONS,
+    };
 
     let transport = std::env::var("TRANSPORT").unwrap_or_else(|_| "stdio".to_string());
+    let network = matches!(transport.as_str(), "sse" | "streamable-http");
+
+    // API keys and OAuth tokens guard the network transports; stdio is only
+    // reachable by whoever started the process.
+    let auth = Auth::from_env()?.map(Arc::new);
+    if auth.is_some() && !network {
+        tracing::warn!("MCP_API_KEYS_FILE/MCP_JWKS_FILE are set but only apply to TRANSPORT=sse or streamable-http");
+    }
+    if auth.is_none() && network {
+        tracing::warn!(
+            "No MCP_API_KEYS_FILE or MCP_JWKS_FILE: anyone who can reach the server can act as the Slack app"
+        );
+    }
+
+    // The Events API endpoints share the MCP HTTP listener, so they need a
+    // network transport.
+    let signing_secret = std::env::var("SLACK_SIGNING_SECRET")
+        .ok()
+        .filter(|s| !s.is_empty());
+    let events_api = signing_secret.is_some() && network;
+    if signing_secret.is_some() && !events_api {
+        tracing::warn!(
+            "SLACK_SIGNING_SECRET is set but the Events API endpoint needs TRANSPORT=sse or streamable-http"
+        );
+    }
+
+    // Socket Mode runs alongside whichever transport serves MCP; both it and
+    // the Events API endpoint feed incoming Slack events to the tools.
+    let socket_mode = client.has_token(TokenKind::App);
+    let events = (socket_mode || events_api).then(EventBus::new);
+    if let (true, Some(bus)) = (socket_mode, &events) {
+        tokio::spawn(SocketMode::new(client.clone(), bus.clone()).run());
+        tracing::info!("Socket Mode enabled");
+    }
+
+    let server = SlackTools::new(client, default_channel)
+        .with_output_mode(output_mode)
+        .with_unowned_edits(allow_unowned_edits)
+        .with_upload_dir(upload_dir)
+        .with_poll_interval(poll_interval)
+        .with_events(events.clone());
+
+    let path = |var: &str, default: &str| {
+        std::env::var(var)
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| default.to_string())
+    };
+    let http_path = path("HTTP_PATH", "/mcp");
+    let mut routes = match transport.as_str() {
+        "sse" => vec![("the SSE stream", "/sse"), ("the SSE message endpoint", "/message")],
+        "streamable-http" => vec![("HTTP_PATH", http_path.as_str())],
+        _ => Vec::new(),
+    };
+
+    let slack_routes = match (signing_secret, &events) {
+        (Some(secret), Some(bus)) if events_api => {
+            let events_path = path("SLACK_EVENTS_PATH", events_api::DEFAULT_PATH);
+            let interactivity_path =
+                path("SLACK_INTERACTIVITY_PATH", events_api::DEFAULT_INTERACTIVITY_PATH);
+            routes.push(("SLACK_EVENTS_PATH", &events_path));
+            routes.push(("SLACK_INTERACTIVITY_PATH", &interactivity_path));
+            check_routes(&routes)?;
+            tracing::info!(
+                "Slack Events API endpoint at {}, interactivity at {}",
+                events_path,
+                interactivity_path
+            );
+            Some(events_api::router(
+                &events_path,
+                &interactivity_path,
+                secret,
+                bus.clone(),
+            ))
+        }
+        _ => {
+            check_routes(&routes)?;
+            None
+        }
+    };
 
     match transport.as_str() {
         "stdio" => {
@@ -40,18 +229,90 @@ async fn main() -> Result<()> {
             let port = std::env::var("SSE_PORT").unwrap_or_else(|_| "8080".to_string());
             let addr: std::net::SocketAddr = format!("{}:{}", host, port).parse()?;
 
+            let (sse, mut router) = SseServer::new(SseServerConfig {
+                bind: addr,
+                sse_path: "/sse".to_string(),
+                post_path: "/message".to_string(),
+                ct: CancellationToken::new(),
+                sse_keep_alive: None,
+            });
+            let sessions = Sessions::new(server, max_sessions);
+            router = guarded(router, &sessions, &auth);
+            if let Some(routes) = slack_routes {
+                router = router.merge(routes);
+            }
+
+            let listener = tokio::net::TcpListener::bind(addr).await?;
             tracing::info!("slack-mcp SSE server listening on {}", addr);
+            let http_ct = sse.config.ct.child_token();
+            let http = axum::serve(listener, router)
+                .with_graceful_shutdown(async move { http_ct.cancelled().await });
+            tokio::spawn(async move {
+                if let Err(e) = http.await {
+                    tracing::error!("SSE server error: {}", e);
+                }
+            });
 
-            let ct = rmcp::transport::sse_server::SseServer::serve(addr)
-                .await?
-                .with_service(move || server.clone());
+            let ct = sse.with_service(move || sessions.session());
 
             tokio::signal::ctrl_c().await?;
             tracing::info!("Shutting down SSE server");
             ct.cancel();
         }
+        "streamable-http" => {
+            let host = std::env::var("HTTP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
+            let port = std::env::var("HTTP_PORT").unwrap_or_else(|_| "8080".to_string());
+            let path = http_path;
+            let addr: std::net::SocketAddr = format!("{}:{}", host, port).parse()?;
+
+            let sessions = Sessions::new(server, max_sessions);
+            let factory = sessions.clone();
+            // Clients that vanish without closing their session would
+            // otherwise hold a slot forever.
+            let session_manager = LocalSessionManager {
+                sessions: Default::default(),
+                session_config: SessionConfig {
+                    keep_alive: Some(sessions::IDLE_TIMEOUT),
+                    ..Default::default()
+                },
+            };
+            let service = StreamableHttpService::new(
+                move || factory.try_session(),
+                Arc::new(session_manager),
+                StreamableHttpServerConfig::default(),
+            );
+            let mut router = guarded(
+                axum::Router::new().route_service(&path, service),
+                &sessions,
+                &auth,
+            );
+            if let Some(routes) = slack_routes {
+                router = router.merge(routes);
+            }
+
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            tracing::info!("slack-mcp streamable HTTP server listening on {}{}", addr, path);
+            let shutdown = CancellationToken::new();
+            let http = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+            let http = tokio::spawn(std::future::IntoFuture::into_future(http));
+
+            tokio::signal::ctrl_c().await?;
+            tracing::info!("Shutting down streamable HTTP server");
+            shutdown.cancel();
+            // Clients holding an SSE stream open would stall a graceful
+            // shutdown indefinitely.
+            match tokio::time::timeout(SHUTDOWN_GRACE, http).await {
+                Ok(Ok(Err(e))) => tracing::error!("Streamable HTTP server error: {}", e),
+                Err(_) => tracing::warn!("Connections still open after {:?}; exiting", SHUTDOWN_GRACE),
+                _ => {}
+            }
+        }
         other => {
-            anyhow::bail!("Unknown TRANSPORT: '{}'. Use 'stdio' or 'sse'.", other);
+            anyhow::bail!(
+                "Unknown TRANSPORT: '{}'. Use 'stdio', 'sse' or 'streamable-http'.",
+                other
+            );
         }
     }
 
@@ -0,0 +1,550 @@
+//! CommonMark → Slack conversion: mrkdwn for inline text, Block Kit for
+//! structure (header blocks, rich_text lists, code and table sections).
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::blocks::{MAX_HEADER_TEXT, MAX_SECTION_TEXT};
+
+/// Room for the ``` fences and newlines around a code chunk.
+const FENCE_OVERHEAD: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageFormat {
+    /// CommonMark, converted to Block Kit (headings, lists, code, tables).
+    Markdown,
+    /// Slack's own mrkdwn, sent as-is.
+    #[default]
+    Mrkdwn,
+    /// Literal text with no formatting.
+    Plain,
+}
+
+/// Escape the three characters Slack treats as control sequences.
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max - 1).collect();
+    out.push('…');
+    out
+}
+
+/// Split mrkdwn `s` into chunks of at most `max` characters, preferring line
+/// breaks, then spaces, over cutting mid-word. Links, mentions (`<…>`) and
+/// entities (`&amp;`) are never cut.
+pub fn split_text(s: &str, max: usize) -> Vec<String> {
+    split(s, max, true)
+}
+
+/// Byte ranges of the `<…>` and `&…;` sequences in `s`, which must stay whole.
+fn markup_spans(s: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < s.len() {
+        let rest = &s[i..];
+        let len = if rest.starts_with('<') {
+            rest.find('>').map(|end| end + 1)
+        } else if let Some(entity) = rest.strip_prefix('&') {
+            entity
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+                .filter(|&end| (1..=8).contains(&end) && entity[end..].starts_with(';'))
+                .map(|end| end + 2)
+        } else {
+            None
+        };
+        match len {
+            Some(len) => {
+                spans.push((i, i + len));
+                i += len;
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    spans
+}
+
+fn split(s: &str, max: usize, markup: bool) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = s;
+    while rest.chars().count() > max {
+        let limit = rest.char_indices().nth(max).map_or(rest.len(), |(i, _)| i);
+        let spans = if markup { markup_spans(rest) } else { Vec::new() };
+        let span_at = |i: usize| spans.iter().find(|&&(start, end)| start < i && i < end);
+        let head = &rest[..limit];
+        let last = |c: char| {
+            head.char_indices()
+                .rev()
+                .find(|&(i, ch)| ch == c && span_at(i).is_none())
+                .map(|(i, _)| i)
+        };
+        let cut = [last('\n'), last(' ')]
+            .into_iter()
+            .flatten()
+            .find(|&i| i > limit / 2)
+            // No room to break at whitespace: cut before a link or entity
+            // rather than through it, unless it alone is too long.
+            .unwrap_or_else(|| match span_at(limit) {
+                Some(&(start, _)) if start > 0 => start,
+                _ => limit,
+            });
+        chunks.push(rest[..cut].trim_end().to_string());
+        rest = rest[cut..].strip_prefix(['\n', ' ']).unwrap_or(&rest[cut..]);
+    }
+    if !rest.trim().is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+/// Split `raw` into chunks that still fit in `max` once escaped. Splitting
+/// the escaped text instead could cut an entity like `&amp;` in half.
+fn split_escaped(raw: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    for chunk in split(raw, max, false) {
+        let escaped = escape(&chunk);
+        let overhead = escaped.chars().count() - chunk.chars().count();
+        if overhead == 0 {
+            chunks.push(escaped);
+            continue;
+        }
+        // A fifth of `max` fits even if every character needs escaping.
+        let budget = max.saturating_sub(overhead).max(max / 5);
+        chunks.extend(split(&chunk, budget, false).iter().map(|c| escape(c)));
+    }
+    chunks
+}
+
+/// Inline content collected in both the mrkdwn and rich_text forms, since a
+/// paragraph becomes a mrkdwn section but a list item becomes rich text.
+#[derive(Default)]
+struct Inline {
+    mrkdwn: String,
+    plain: String,
+    rich: Vec<Value>,
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    /// Destination and text of the link being read.
+    link: Option<(String, String)>,
+}
+
+impl Inline {
+    fn is_empty(&self) -> bool {
+        self.plain.trim().is_empty()
+    }
+
+    fn style(&self, code: bool) -> Option<Value> {
+        let mut style = Map::new();
+        for (key, on) in [
+            ("bold", self.bold),
+            ("italic", self.italic),
+            ("strike", self.strike),
+            ("code", code),
+        ] {
+            if on {
+                style.insert(key.to_string(), json!(true));
+            }
+        }
+        (!style.is_empty()).then_some(Value::Object(style))
+    }
+
+    fn push_rich(&mut self, text: &str, code: bool) {
+        let mut element = json!({ "type": "text", "text": text });
+        if let Some(style) = self.style(code) {
+            element["style"] = style;
+        }
+        self.rich.push(element);
+    }
+
+    fn text(&mut self, s: &str) {
+        self.plain.push_str(s);
+        if let Some((_, text)) = &mut self.link {
+            text.push_str(s);
+            return;
+        }
+        self.mrkdwn.push_str(&escape(s));
+        self.push_rich(s, false);
+    }
+
+    fn code(&mut self, s: &str) {
+        self.plain.push_str(s);
+        if let Some((_, text)) = &mut self.link {
+            text.push_str(s);
+            return;
+        }
+        self.mrkdwn.push_str(&format!("`{}`", escape(s)));
+        self.push_rich(s, true);
+    }
+
+    fn mark(&mut self, marker: &str) {
+        if self.link.is_none() {
+            self.mrkdwn.push_str(marker);
+        }
+    }
+
+    fn end_link(&mut self) {
+        let Some((url, text)) = self.link.take() else {
+            return;
+        };
+        let text = if text.is_empty() { url.clone() } else { text };
+        self.mrkdwn.push_str(&format!("<{}|{}>", url, escape(&text)));
+        self.rich.push(json!({ "type": "link", "url": url, "text": text }));
+    }
+}
+
+#[derive(Default)]
+struct Renderer {
+    blocks: Vec<Value>,
+    inline: Inline,
+    quote_depth: usize,
+    code: Option<String>,
+    /// Open lists, innermost last: (ordered start number, items so far).
+    lists: Vec<(Option<u64>, u64)>,
+    /// rich_text_list elements of the list being built.
+    list_elements: Vec<Value>,
+    table: Option<Vec<Vec<String>>>,
+    /// Whether the last block is a paragraph section later paragraphs may join.
+    paragraph_run: bool,
+}
+
+impl Renderer {
+    fn push_block(&mut self, block: Value) {
+        self.blocks.push(block);
+        self.paragraph_run = false;
+    }
+
+    fn push_section(&mut self, text: &str, paragraph: bool) {
+        for chunk in split_text(text, MAX_SECTION_TEXT) {
+            if paragraph && self.paragraph_run {
+                let last = self.blocks.last_mut().unwrap();
+                let joined = format!("{}\n\n{}", last["text"]["text"].as_str().unwrap(), chunk);
+                if joined.chars().count() <= MAX_SECTION_TEXT {
+                    last["text"]["text"] = json!(joined);
+                    continue;
+                }
+            }
+            self.push_block(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": chunk },
+            }));
+            self.paragraph_run = paragraph;
+        }
+    }
+
+    fn push_preformatted(&mut self, text: &str) {
+        let text = text.trim_end_matches('\n');
+        for chunk in split_escaped(text, MAX_SECTION_TEXT - FENCE_OVERHEAD) {
+            self.push_section(&format!("```\n{}\n```", chunk), false);
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        let inline = std::mem::take(&mut self.inline);
+        if inline.is_empty() {
+            return;
+        }
+        let text = if self.quote_depth > 0 {
+            let prefix = "> ".repeat(self.quote_depth);
+            inline
+                .mrkdwn
+                .lines()
+                .map(|l| format!("{}{}", prefix, l))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            inline.mrkdwn
+        };
+        self.push_section(&text, true);
+    }
+
+    /// Move the text read so far for the current list item into the list.
+    fn end_item(&mut self) {
+        let inline = std::mem::take(&mut self.inline);
+        if inline.is_empty() {
+            return;
+        }
+        let indent = self.lists.len() - 1;
+        let (start, count) = self.lists[indent];
+        self.lists[indent].1 += 1;
+        let style = if start.is_some() { "ordered" } else { "bullet" };
+        let section = json!({ "type": "rich_text_section", "elements": inline.rich });
+
+        if let Some(last) = self.list_elements.last_mut() {
+            if last["indent"] == json!(indent) && last["style"] == style {
+                last["elements"].as_array_mut().unwrap().push(section);
+                return;
+            }
+        }
+        let mut list = json!({
+            "type": "rich_text_list",
+            "style": style,
+            "indent": indent,
+            "elements": [section],
+        });
+        // A list resumed after a nested one keeps counting where it left off.
+        let offset = start.unwrap_or(1).saturating_sub(1) + count;
+        if start.is_some() && offset > 0 {
+            list["offset"] = json!(offset);
+        }
+        self.list_elements.push(list);
+    }
+
+    /// Put the list elements built so far in a rich_text block.
+    fn flush_list(&mut self) {
+        let elements = std::mem::take(&mut self.list_elements);
+        if !elements.is_empty() {
+            self.push_block(json!({ "type": "rich_text", "elements": elements }));
+        }
+    }
+
+    fn end_table(&mut self, rows: Vec<Vec<String>>) {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|c| {
+                rows.iter()
+                    .filter_map(|r| r.get(c))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |row: &Vec<String>| {
+            widths
+                .iter()
+                .enumerate()
+                .map(|(c, w)| {
+                    let cell = row.get(c).map_or("", String::as_str);
+                    format!("{}{}", cell, " ".repeat(w - cell.chars().count()))
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+                .trim_end()
+                .to_string()
+        };
+        let mut lines: Vec<String> = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            lines.push(line(row));
+            if i == 0 {
+                let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                lines.push(rule.join("-+-"));
+            }
+        }
+        self.push_preformatted(&lines.join("\n"));
+    }
+
+    fn event(&mut self, event: Event) {
+        if let Some(code) = &mut self.code {
+            match event {
+                Event::Text(s) => code.push_str(&s),
+                Event::End(TagEnd::CodeBlock) => {
+                    let code = self.code.take().unwrap();
+                    self.push_preformatted(&code);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            // A second paragraph in a loose list item continues the same item.
+            Event::Start(Tag::Paragraph) if !self.lists.is_empty() && !self.inline.is_empty() => {
+                self.inline.text("\n");
+            }
+            Event::End(TagEnd::Paragraph) if self.lists.is_empty() => self.end_paragraph(),
+            Event::End(TagEnd::Heading(_)) => {
+                let inline = std::mem::take(&mut self.inline);
+                let text = inline.plain.trim();
+                if !text.is_empty() {
+                    self.push_block(json!({
+                        "type": "header",
+                        "text": { "type": "plain_text", "text": truncate(text, MAX_HEADER_TEXT) },
+                    }));
+                }
+            }
+            Event::Start(Tag::BlockQuote(_)) => self.quote_depth += 1,
+            Event::End(TagEnd::BlockQuote(_)) => self.quote_depth -= 1,
+            Event::Start(Tag::CodeBlock(_)) => {
+                // Code can't go in a rich_text list: close the list so far,
+                // and the next item resumes it after the code.
+                if self.lists.is_empty() {
+                    self.end_paragraph();
+                } else {
+                    self.end_item();
+                    self.flush_list();
+                }
+                self.code = Some(String::new());
+            }
+            Event::Start(Tag::List(start)) => {
+                if self.lists.is_empty() {
+                    self.end_paragraph();
+                } else {
+                    self.end_item();
+                }
+                self.lists.push((start, 0));
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.flush_list();
+                }
+            }
+            Event::End(TagEnd::Item) => self.end_item(),
+            Event::Start(Tag::Table(_)) => self.table = Some(Vec::new()),
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => {
+                if let Some(rows) = &mut self.table {
+                    rows.push(Vec::new());
+                }
+            }
+            Event::End(TagEnd::TableCell) => {
+                let cell = std::mem::take(&mut self.inline).plain.trim().to_string();
+                if let Some(row) = self.table.as_mut().and_then(|rows| rows.last_mut()) {
+                    row.push(cell);
+                }
+            }
+            Event::End(TagEnd::Table) => {
+                let rows = self.table.take().unwrap_or_default();
+                self.end_table(rows);
+            }
+            Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) => {
+                self.inline.italic = matches!(event, Event::Start(_));
+                self.inline.mark("_");
+            }
+            Event::Start(Tag::Strong) | Event::End(TagEnd::Strong) => {
+                self.inline.bold = matches!(event, Event::Start(_));
+                self.inline.mark("*");
+            }
+            Event::Start(Tag::Strikethrough) | Event::End(TagEnd::Strikethrough) => {
+                self.inline.strike = matches!(event, Event::Start(_));
+                self.inline.mark("~");
+            }
+            Event::Start(Tag::Link { dest_url, .. }) | Event::Start(Tag::Image { dest_url, .. }) => {
+                self.inline.link = Some((dest_url.to_string(), String::new()));
+            }
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => self.inline.end_link(),
+            Event::Text(s) | Event::Html(s) | Event::InlineHtml(s) => self.inline.text(&s),
+            Event::Code(s) => self.inline.code(&s),
+            Event::SoftBreak | Event::HardBreak => self.inline.text("\n"),
+            Event::TaskListMarker(done) => self.inline.text(if done { "☑ " } else { "☐ " }),
+            Event::Rule => {
+                self.end_paragraph();
+                self.push_block(json!({ "type": "divider" }));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Convert CommonMark to Block Kit blocks. Long paragraphs and code are split
+/// across sections so each stays within Slack's per-block limits; the caller
+/// splits the block list across messages if it exceeds the per-message limit.
+pub fn to_blocks(markdown: &str) -> Vec<Value> {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut renderer = Renderer::default();
+    for event in Parser::new_ext(markdown, options) {
+        renderer.event(event);
+    }
+    renderer.end_paragraph();
+    renderer.blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::validate_blocks;
+
+    #[test]
+    fn converts_inline_and_structure() {
+        let blocks = to_blocks(
+            "# Release notes\n\n\
+             Ship **v2** with _care_ ~~today~~, see [the doc](https://x.dev/a) & `make <all>`.\n\n\
+             - one\n- **two**\n  1. nested\n- three\n\n\
+             ```rust\nfn main() {}\n```\n\n\
+             ---\n\n\
+             > quoted\n",
+        );
+        validate_blocks(&blocks, "blocks").unwrap();
+
+        assert_eq!(blocks[0], json!({ "type": "header", "text": { "type": "plain_text", "text": "Release notes" } }));
+        assert_eq!(
+            blocks[1]["text"]["text"],
+            "Ship *v2* with _care_ ~today~, see <https://x.dev/a|the doc> &amp; `make &lt;all&gt;`."
+        );
+
+        let lists = blocks[2]["elements"].as_array().unwrap();
+        assert_eq!(blocks[2]["type"], "rich_text");
+        assert_eq!(lists.len(), 3);
+        assert_eq!(lists[0]["style"], "bullet");
+        assert_eq!(lists[0]["elements"].as_array().unwrap().len(), 2);
+        assert_eq!(lists[0]["elements"][1]["elements"][0]["style"]["bold"], true);
+        assert_eq!(lists[1]["style"], "ordered");
+        assert_eq!(lists[1]["indent"], 1);
+        assert_eq!(lists[2]["elements"][0]["elements"][0]["text"], "three");
+
+        assert_eq!(blocks[3]["text"]["text"], "```\nfn main() {}\n```");
+        assert_eq!(blocks[4]["type"], "divider");
+        assert_eq!(blocks[5]["text"]["text"], "> quoted");
+    }
+
+    #[test]
+    fn code_in_a_list_item_splits_the_list_around_it() {
+        let blocks = to_blocks("- first\n\n  ```\n  code\n  ```\n- second\n");
+        validate_blocks(&blocks, "blocks").unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0]["elements"][0]["elements"][0]["elements"][0]["text"], "first");
+        assert_eq!(blocks[1]["text"]["text"], "```\ncode\n```");
+        assert_eq!(blocks[2]["elements"][0]["elements"][0]["elements"][0]["text"], "second");
+
+        // An ordered list keeps counting after the code.
+        let blocks = to_blocks("1. first\n\n   ```\n   code\n   ```\n2. second\n");
+        assert_eq!(blocks[0]["elements"][0]["style"], "ordered");
+        assert_eq!(blocks[2]["elements"][0]["offset"], 1);
+    }
+
+    #[test]
+    fn tables_fall_back_to_aligned_text() {
+        let blocks = to_blocks("| Service | State |\n|---|---|\n| api | up |\n| db | degraded |\n");
+        assert_eq!(
+            blocks[0]["text"]["text"],
+            "```\nService | State\n--------+---------\napi     | up\ndb      | degraded\n```"
+        );
+    }
+
+    #[test]
+    fn long_text_is_split_across_sections() {
+        let paragraph = "word ".repeat(1000);
+        let blocks = to_blocks(&paragraph);
+        validate_blocks(&blocks, "blocks").unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|b| b["type"] == "section"));
+
+        assert_eq!(split_text("aaaa bbbb\ncccc", 10), ["aaaa bbbb", "cccc"]);
+
+        // A link straddling the limit moves whole to the next section.
+        let paragraph = format!(
+            "{} [the full report](https://example.com/a) & more",
+            "x".repeat(MAX_SECTION_TEXT - 10)
+        );
+        let blocks = to_blocks(&paragraph);
+        validate_blocks(&blocks, "blocks").unwrap();
+        assert_eq!(
+            blocks[1]["text"]["text"],
+            "<https://example.com/a|the full report> &amp; more"
+        );
+        assert_eq!(split_text("ab&amp;cd", 5), ["ab", "&amp;", "cd"]);
+
+        let chunks = split_escaped(&"a<b ".repeat(10), 12);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 12));
+        let words: Vec<&str> = chunks.iter().flat_map(|c| c.split_whitespace()).collect();
+        assert_eq!(words, ["a&lt;b"; 10]);
+        let code = format!("```\n{}\n```", "x && y\n".repeat(600));
+        validate_blocks(&to_blocks(&code), "blocks").unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+//! Record of messages this server posted, so edits and deletes can be limited
+//! to messages it owns.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// Oldest entries are forgotten past this many, to bound memory.
+const MAX_TRACKED: usize = 10_000;
+
+type Key = (String, String);
+
+#[derive(Default)]
+struct Inner {
+    order: VecDeque<Key>,
+    set: HashSet<Key>,
+}
+
+#[derive(Default)]
+pub struct PostedMessages {
+    inner: Mutex<Inner>,
+}
+
+impl PostedMessages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, channel: &str, ts: &str) {
+        let key = (channel.to_string(), ts.to_string());
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.set.insert(key.clone()) {
+            return;
+        }
+        inner.order.push_back(key);
+        if inner.order.len() > MAX_TRACKED {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.set.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn contains(&self, channel: &str, ts: &str) -> bool {
+        let key = (channel.to_string(), ts.to_string());
+        self.inner.lock().unwrap().set.contains(&key)
+    }
+
+    pub fn forget(&self, channel: &str, ts: &str) {
+        let key = (channel.to_string(), ts.to_string());
+        let mut inner = self.inner.lock().unwrap();
+        if inner.set.remove(&key) {
+            inner.order.retain(|k| *k != key);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Slack Web API rate limit tiers.
+///
+/// See https://api.slack.com/apis/rate-limits. `PostMessage` is the special
+/// "roughly one message per second per channel" limit on `chat.postMessage`,
+/// and `Connections` the separate allowance for Socket Mode's
+/// `apps.connections.open`, which reconnects must not queue behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    Tier2,
+    Tier3,
+    Tier4,
+    PostMessage,
+    Connections,
+}
+
+impl Tier {
+    pub fn for_method(method: &str) -> Self {
+        match method {
+            "chat.postMessage" => Tier::PostMessage,
+            "apps.connections.open" => Tier::Connections,
+            "conversations.list" | "users.list" | "lists.items.list" | "search.messages" => {
+                Tier::Tier2
+            }
+            "conversations.history"
+            | "conversations.replies"
+            | "conversations.info"
+            | "conversations.open"
+            | "conversations.join"
+            | "chat.update"
+            | "chat.delete"
+            | "chat.postEphemeral"
+            | "chat.scheduleMessage"
+            | "chat.scheduledMessages.list"
+            | "chat.deleteScheduledMessage"
+            | "reactions.add"
+            | "canvases.create"
+            | "canvases.edit"
+            | "conversations.canvases.create"
+            | "lists.create"
+            | "lists.update"
+            | "lists.items.create"
+            | "lists.items.update"
+            | "lists.items.delete"
+            | "lists.items.deleteMultiple"
+            | "lists.access.set"
+            | "lists.access.delete" => Tier::Tier3,
+            "users.info" | "lists.items.info" | "auth.test" => Tier::Tier4,
+            // Slack documents most other methods as Tier 3.
+            _ => Tier::Tier3,
+        }
+    }
+
+    /// Burst capacity and the time it takes to refill it completely.
+    fn quota(self) -> (f64, Duration) {
+        match self {
+            Tier::Tier2 => (20.0, Duration::from_secs(60)),
+            Tier::Tier3 => (50.0, Duration::from_secs(60)),
+            Tier::Tier4 => (100.0, Duration::from_secs(60)),
+            Tier::PostMessage => (1.0, Duration::from_secs(1)),
+            Tier::Connections => (5.0, Duration::from_secs(60)),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    blocked_until: Option<Instant>,
+}
+
+/// Client-side token buckets keyed by Slack method (and by channel for
+/// `chat.postMessage`), so concurrent tool calls queue up locally instead of
+/// all tripping Slack's limits at once.
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bucket key for a request: the method name, plus the channel for
+    /// `chat.postMessage` since that limit applies per channel.
+    pub fn key(method: &str, body: &Value) -> String {
+        match (
+            Tier::for_method(method),
+            body.get("channel").and_then(|c| c.as_str()),
+        ) {
+            (Tier::PostMessage, Some(channel)) => format!("{}:{}", method, channel),
+            _ => method.to_string(),
+        }
+    }
+
+    /// Wait until a request for `key` may be sent and reserve a slot for it.
+    pub async fn acquire(&self, key: &str, tier: Tier) {
+        let (capacity, period) = tier.quota();
+        let per_sec = capacity / period.as_secs_f64();
+
+        let wait = {
+            let mut buckets = self.buckets.lock().await;
+            let now = Instant::now();
+            let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+                blocked_until: None,
+            });
+
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * per_sec).min(capacity);
+            bucket.updated = now;
+
+            // Reserve the slot up front; a negative balance is how later callers
+            // learn they are queued behind this one.
+            bucket.tokens -= 1.0;
+            let mut wait = if bucket.tokens >= 0.0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64(-bucket.tokens / per_sec)
+            };
+            if let Some(until) = bucket.blocked_until {
+                wait = wait.max(until.saturating_duration_since(now));
+            }
+            wait
+        };
+
+        if !wait.is_zero() {
+            tracing::debug!("Rate limiting {}: waiting {:?}", key, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Record a 429 from Slack: every caller on `key` waits out `retry_after`.
+    pub async fn penalize(&self, key: &str, retry_after: Duration) {
+        let mut buckets = self.buckets.lock().await;
+        let now = Instant::now();
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: 0.0,
+            updated: now,
+            blocked_until: None,
+        });
+        let until = now + retry_after;
+        bucket.blocked_until = Some(bucket.blocked_until.map_or(until, |b| b.max(until)));
+    }
+}
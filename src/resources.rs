@@ -0,0 +1,326 @@
+//! MCP resources: channels, threads, canvases and lists under `slack://` URIs.
+
+use rmcp::model::{
+    AnnotateAble, ErrorData, ListResourceTemplatesResult, ListResourcesResult, RawResource,
+    RawResourceTemplate, ReadResourceResult, ResourceContents,
+};
+use serde_json::{json, Value};
+
+use crate::directory::Directory;
+use crate::error::SlackError;
+use crate::shape::{self, OutputMode};
+use crate::slack_client::SlackClient;
+
+/// Messages included when a channel is read as a resource.
+const CHANNEL_HISTORY: u32 = 50;
+/// Channels listed per resources/list page.
+const CHANNELS_PER_PAGE: u32 = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SlackUri {
+    Channel(String),
+    Thread { channel: String, ts: String },
+    Canvas(String),
+    List(String),
+}
+
+impl SlackUri {
+    pub fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix("slack://")?;
+        let parts: Vec<&str> = rest.split('/').collect();
+        let uri = match parts.as_slice() {
+            ["channel", id] => SlackUri::Channel(id.to_string()),
+            ["thread", channel, ts] => SlackUri::Thread {
+                channel: channel.to_string(),
+                ts: ts.to_string(),
+            },
+            ["canvas", id] => SlackUri::Canvas(id.to_string()),
+            ["list", id] => SlackUri::List(id.to_string()),
+            _ => return None,
+        };
+        let ids = match &uri {
+            SlackUri::Channel(id) | SlackUri::Canvas(id) | SlackUri::List(id) => vec![id],
+            SlackUri::Thread { channel, ts } => vec![channel, ts],
+        };
+        ids.iter().all(|s| !s.is_empty()).then_some(uri)
+    }
+}
+
+impl std::fmt::Display for SlackUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlackUri::Channel(id) => write!(f, "slack://channel/{}", id),
+            SlackUri::Thread { channel, ts } => write!(f, "slack://thread/{}/{}", channel, ts),
+            SlackUri::Canvas(id) => write!(f, "slack://canvas/{}", id),
+            SlackUri::List(id) => write!(f, "slack://list/{}", id),
+        }
+    }
+}
+
+pub fn templates() -> ListResourceTemplatesResult {
+    let template = |uri: &str, name: &str, description: &str, mime: &str| {
+        RawResourceTemplate {
+            uri_template: uri.to_string(),
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            mime_type: Some(mime.to_string()),
+        }
+        .no_annotation()
+    };
+    ListResourceTemplatesResult::with_all_items(vec![
+        template(
+            "slack://channel/{id}",
+            "Slack channel",
+            "Recent messages in a channel, oldest first",
+            "text/plain",
+        ),
+        template(
+            "slack://thread/{channel}/{ts}",
+            "Slack thread",
+            "A message and all its replies",
+            "text/plain",
+        ),
+        template(
+            "slack://canvas/{id}",
+            "Slack canvas",
+            "The contents of a canvas",
+            "text/plain",
+        ),
+        template(
+            "slack://list/{id}",
+            "Slack list",
+            "The items of a Slack list",
+            "application/json",
+        ),
+    ])
+}
+
+/// One page of channels the app is a member of, as `slack://channel/{id}`.
+pub async fn list(client: &SlackClient, cursor: Option<String>) -> Result<ListResourcesResult, SlackError> {
+    let mut body = json!({
+        "types": "public_channel,private_channel",
+        "exclude_archived": true,
+        "limit": CHANNELS_PER_PAGE,
+    });
+    if let Some(cursor) = cursor {
+        body["cursor"] = json!(cursor);
+    }
+    let data = client.call("conversations.list", body).await?;
+    let resources = data["channels"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        // Reading history needs membership; Slack omits is_member for user tokens.
+        .filter(|c| c["is_member"] != json!(false))
+        .filter_map(|c| {
+            let id = c["id"].as_str()?;
+            let mut resource = RawResource::new(
+                SlackUri::Channel(id.to_string()).to_string(),
+                format!("#{}", c["name"].as_str().unwrap_or(id)),
+            );
+            resource.description = [c.pointer("/topic/value"), c.pointer("/purpose/value")]
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+                .find(|s| !s.is_empty())
+                .map(String::from);
+            resource.mime_type = Some("text/plain".to_string());
+            Some(resource.no_annotation())
+        })
+        .collect();
+    let next_cursor = data
+        .pointer("/response_metadata/next_cursor")
+        .and_then(|c| c.as_str())
+        .filter(|c| !c.is_empty())
+        .map(String::from);
+    Ok(ListResourcesResult {
+        next_cursor,
+        resources,
+    })
+}
+
+/// Render messages (newest first, as Slack returns history) oldest first.
+async fn messages_text(
+    client: &SlackClient,
+    directory: &Directory,
+    mut messages: Value,
+    newest_first: bool,
+) -> String {
+    directory.resolve_messages(client, &mut messages).await;
+    if newest_first {
+        if let Some(list) = messages.as_array_mut() {
+            list.reverse();
+        }
+    }
+    shape::render(json!({ "messages": messages }), OutputMode::Text)
+}
+
+pub async fn read(
+    client: &SlackClient,
+    directory: &Directory,
+    uri: &str,
+) -> Result<ReadResourceResult, ErrorData> {
+    let parsed = SlackUri::parse(uri).ok_or_else(|| {
+        ErrorData::resource_not_found(
+            format!("Unknown resource URI: {}", uri),
+            Some(json!({
+                "hint": "Use slack://channel/{id}, slack://thread/{channel}/{ts}, \
+                         slack://canvas/{id} or slack://list/{id}.",
+            })),
+        )
+    })?;
+
+    let (mime_type, text) = match parsed {
+        SlackUri::Channel(id) => {
+            let data = client
+                .call(
+                    "conversations.history",
+                    json!({ "channel": id, "limit": CHANNEL_HISTORY }),
+                )
+                .await?;
+            let text = messages_text(client, directory, data["messages"].clone(), true).await;
+            ("text/plain", text)
+        }
+        SlackUri::Thread { channel, ts } => {
+            let page = client
+                .paginate(
+                    "conversations.replies",
+                    json!({ "channel": channel, "ts": ts }),
+                    "messages",
+                    Default::default(),
+                )
+                .await?;
+            let text = messages_text(client, directory, json!(page.items), false).await;
+            ("text/plain", text)
+        }
+        SlackUri::Canvas(id) => {
+            let info = client.call("files.info", json!({ "file": id })).await?;
+            let url = info["file"]["url_private"].as_str().ok_or_else(|| {
+                ErrorData::resource_not_found(format!("Canvas {} has no readable content", id), None)
+            })?;
+            let (bytes, content_type) = client.download(None, url).await?;
+            // Passed on unconverted, so label it as what Slack served.
+            let mime_type = match content_type.as_deref().and_then(|t| t.split(';').next()) {
+                Some("text/html") => "text/html",
+                Some("application/json") => "application/json",
+                _ => "text/plain",
+            };
+            (mime_type, String::from_utf8_lossy(&bytes).into_owned())
+        }
+        SlackUri::List(id) => {
+            let page = client
+                .paginate(
+                    "lists.items.list",
+                    json!({ "list_id": id }),
+                    "items",
+                    Default::default(),
+                )
+                .await?;
+            let data = shape::compact(json!({ "items": page.items }));
+            ("application/json", data.to_string())
+        }
+    };
+
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type.to_string()),
+            text,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::MockSlack;
+
+    fn text_of(result: ReadResourceResult) -> String {
+        match &result.contents[0] {
+            ResourceContents::TextResourceContents { text, .. } => text.clone(),
+            other => panic!("expected text contents, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_and_reads_channels_and_threads() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general").add_user("U001", "alice");
+        let parent = mock.add_message("C001", "U001", "first");
+        mock.add_message("C001", "U001", "second");
+        let client = mock.client();
+        let directory = Directory::new();
+
+        let listed = list(&client, None).await.unwrap();
+        assert_eq!(listed.resources[0].uri, "slack://channel/C001");
+        assert_eq!(listed.resources[0].name, "#general");
+
+        let text = text_of(read(&client, &directory, "slack://channel/C001").await.unwrap());
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].ends_with("@alice: first"), "{}", text);
+        assert!(lines[1].ends_with("@alice: second"), "{}", text);
+
+        let uri = format!("slack://thread/C001/{}", parent);
+        let text = text_of(read(&client, &directory, &uri).await.unwrap());
+        assert!(text.contains("first"));
+
+        let err = read(&client, &directory, "slack://nope/1").await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn reads_canvases_and_lists() {
+        let mock = MockSlack::start().await;
+        let client = mock.client();
+        let directory = Directory::new();
+
+        let canvas = client
+            .post(
+                "canvases.create",
+                json!({ "title": "Plan", "document_content": { "type": "markdown", "markdown": "# Q3" } }),
+            )
+            .await
+            .unwrap();
+        let uri = format!("slack://canvas/{}", canvas["canvas_id"].as_str().unwrap());
+        let result = read(&client, &directory, &uri).await.unwrap();
+        let ResourceContents::TextResourceContents { mime_type, .. } = &result.contents[0] else {
+            panic!("expected text contents");
+        };
+        assert_eq!(mime_type.as_deref(), Some("text/plain"));
+        assert_eq!(text_of(result), "# Q3");
+
+        let list_id = client
+            .post("lists.create", json!({ "name": "Tasks", "description": "" }))
+            .await
+            .unwrap()["list_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        client
+            .post(
+                "lists.items.create",
+                json!({ "list_id": list_id, "initial_fields": { "Col1": "ship" } }),
+            )
+            .await
+            .unwrap();
+        let uri = format!("slack://list/{}", list_id);
+        let items: Value =
+            serde_json::from_str(&text_of(read(&client, &directory, &uri).await.unwrap())).unwrap();
+        assert_eq!(items["items"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn uris_round_trip() {
+        for uri in [
+            "slack://channel/C001",
+            "slack://thread/C001/1700000000.000001",
+            "slack://canvas/F001",
+            "slack://list/F002",
+        ] {
+            assert_eq!(SlackUri::parse(uri).unwrap().to_string(), uri);
+        }
+        assert_eq!(SlackUri::parse("slack://channel/"), None);
+        assert_eq!(SlackUri::parse("slack://user/U001"), None);
+        assert_eq!(SlackUri::parse("file:///etc/passwd"), None);
+    }
+}
@@ -0,0 +1,102 @@
+//! Parsing of `post_at` times for scheduled messages.
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Slack refuses to schedule messages further out than this.
+const MAX_DAYS_AHEAD: i64 = 120;
+
+/// When to post: a Unix timestamp, or an ISO-8601 time with a time zone.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PostAt {
+    Unix(i64),
+    Text(String),
+}
+
+/// ISO-8601 layouts accepted besides full RFC 3339 (which needs seconds).
+const FORMATS: &[&str] = &["%Y-%m-%dT%H:%M%#z", "%Y-%m-%d %H:%M%#z", "%Y-%m-%d %H:%M:%S%#z"];
+
+fn parse_iso(s: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .or_else(|| FORMATS.iter().find_map(|f| DateTime::parse_from_str(s, f).ok()))
+}
+
+impl PostAt {
+    /// Resolve to a Unix timestamp that Slack will accept relative to `now`:
+    /// in the future and at most 120 days ahead.
+    pub fn resolve(&self, now: DateTime<Utc>) -> Result<i64, String> {
+        let ts = match self {
+            PostAt::Unix(ts) => *ts,
+            PostAt::Text(s) => {
+                let s = s.trim();
+                match s.parse::<i64>() {
+                    Ok(ts) => ts,
+                    Err(_) => parse_iso(s).map(|t| t.timestamp()).ok_or_else(|| {
+                        format!(
+                            "Cannot read post_at '{}'. Use a Unix timestamp or an ISO-8601 time \
+                             with a time zone, e.g. 2025-06-02T09:00:00-04:00 or 2025-06-02T13:00Z.",
+                            s
+                        )
+                    })?,
+                }
+            }
+        };
+        let when = Utc
+            .timestamp_opt(ts, 0)
+            .single()
+            .ok_or_else(|| format!("post_at {} is out of range", ts))?;
+        if when <= now {
+            return Err(format!("post_at {} is in the past", when.to_rfc3339()));
+        }
+        if when > now + chrono::Duration::days(MAX_DAYS_AHEAD) {
+            return Err(format!(
+                "post_at {} is more than {} days ahead, which Slack does not allow",
+                when.to_rfc3339(),
+                MAX_DAYS_AHEAD
+            ));
+        }
+        Ok(ts)
+    }
+}
+
+/// Render a Unix timestamp as an RFC 3339 UTC time, for tool output.
+pub fn to_rfc3339(ts: i64) -> Option<String> {
+    Utc.timestamp_opt(ts, 0).single().map(|t| t.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_unix_and_iso_times() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let monday_9am_ny = 1748869200; // 2025-06-02T09:00:00-04:00
+
+        let inputs = [
+            PostAt::Unix(monday_9am_ny),
+            PostAt::Text(monday_9am_ny.to_string()),
+            PostAt::Text("2025-06-02T09:00:00-04:00".to_string()),
+            PostAt::Text("2025-06-02T13:00Z".to_string()),
+            PostAt::Text("2025-06-02 15:00+02:00".to_string()),
+        ];
+        for input in inputs {
+            assert_eq!(input.resolve(now), Ok(monday_9am_ny), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_times() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let err = |s: &str| PostAt::Text(s.to_string()).resolve(now).unwrap_err();
+
+        assert!(err("next monday").contains("Cannot read"));
+        // No time zone: ambiguous, so refused.
+        assert!(err("2025-06-02T09:00:00").contains("Cannot read"));
+        assert!(err("2025-05-01T09:00:00Z").contains("in the past"));
+        assert!(err("2026-01-01T09:00:00Z").contains("120 days"));
+    }
+}
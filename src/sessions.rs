@@ -0,0 +1,405 @@
+//! Multi-tenant sessions for the network transports. Each MCP session is
+//! bound on its first request to the Slack token its client brings, in the
+//! X-Slack-Token header or with its API key. Sessions with the same token
+//! share one `SlackClient`, with its rate limits, and one set of caches.
+//! Sessions that bring none act as the server's own tokens.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use rmcp::model::*;
+use rmcp::service::RequestContext;
+use rmcp::{RoleServer, ServerHandler};
+use sha2::{Digest, Sha256};
+use tokio::sync::{OnceCell, OwnedSemaphorePermit, Semaphore};
+
+use crate::auth::Principal;
+use crate::tools::SlackTools;
+
+/// Header a client sends its own Slack token in.
+pub const TOKEN_HEADER: &str = "x-slack-token";
+
+/// Concurrent sessions allowed unless MCP_MAX_SESSIONS says otherwise.
+pub const DEFAULT_MAX_SESSIONS: usize = 100;
+
+/// Streamable HTTP sessions idle this long are closed, freeing their slot.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Who a session belongs to. Every later request must come from the same
+/// caller with the same Slack token.
+#[derive(Debug, PartialEq)]
+struct Owner {
+    principal: Option<String>,
+    token: Option<[u8; 32]>,
+}
+
+struct Bound {
+    owner: Owner,
+    tools: SlackTools,
+}
+
+/// Hands out sessions, up to `max` open at once.
+#[derive(Clone)]
+pub struct Sessions {
+    shared: SlackTools,
+    /// The tools for each client-supplied token, by its digest.
+    tenants: Arc<Mutex<HashMap<[u8; 32], SlackTools>>>,
+    slots: Arc<Semaphore>,
+    max: usize,
+}
+
+impl Sessions {
+    /// `shared` serves sessions without a token of their own.
+    pub fn new(shared: SlackTools, max: usize) -> Self {
+        Self {
+            shared,
+            tenants: Arc::default(),
+            slots: Arc::new(Semaphore::new(max)),
+            max,
+        }
+    }
+
+    /// The handler for a new session; it binds on its first request. It
+    /// holds a slot until it is dropped with its transport, and without a
+    /// free one it refuses to initialize.
+    pub fn session(&self) -> Session {
+        let slot = self.slots.clone().try_acquire_owned().ok();
+        if slot.is_none() {
+            tracing::warn!("Refused a new session: all {} slots are taken", self.max);
+        }
+        Session {
+            sessions: self.clone(),
+            bound: OnceCell::new(),
+            slot,
+        }
+    }
+
+    /// Like `session`, but refuses outright when every slot is taken, for
+    /// transports whose session factory can fail.
+    pub fn try_session(&self) -> io::Result<Session> {
+        let session = self.session();
+        match session.slot {
+            Some(_) => Ok(session),
+            None => Err(io::Error::other(self.full())),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.available_permits() == 0
+    }
+
+    fn full(&self) -> String {
+        format!("Too many sessions (at most {}); try again later", self.max)
+    }
+
+    async fn open(&self, parts: Option<&Parts>) -> Result<Bound, ErrorData> {
+        let (owner, token) = identify(parts)?;
+        let tools = match (token, owner.token) {
+            (Some(token), Some(digest)) => self.tenant(digest, token).await?.for_session(),
+            _ => self.shared.for_session(),
+        };
+        tracing::info!(
+            "Session opened for {} with {}",
+            owner.principal.as_deref().unwrap_or("an anonymous client"),
+            if owner.token.is_some() { "its own Slack token" } else { "the server's Slack token" }
+        );
+        Ok(Bound { owner, tools })
+    }
+
+    /// The tools every session with this token shares, set up once Slack
+    /// accepts the token.
+    async fn tenant(&self, digest: [u8; 32], token: String) -> Result<SlackTools, ErrorData> {
+        if let Some(tools) = self.tenants.lock().unwrap().get(&digest) {
+            return Ok(tools.clone());
+        }
+        let tools = self.shared.for_tenant(token).await.map_err(ErrorData::from)?;
+        let mut tenants = self.tenants.lock().unwrap();
+        // Forget tokens whose sessions have all closed.
+        tenants.retain(|_, tools| tools.is_shared());
+        Ok(tenants.entry(digest).or_insert(tools).clone())
+    }
+}
+
+/// The caller and Slack token behind a request. The header wins over a
+/// token configured with the API key.
+fn identify(parts: Option<&Parts>) -> Result<(Owner, Option<String>), ErrorData> {
+    let principal = parts.and_then(|p| p.extensions.get::<Principal>());
+    let header = match parts.and_then(|p| p.headers.get(TOKEN_HEADER)) {
+        Some(value) => Some(
+            value
+                .to_str()
+                .map_err(|_| ErrorData::invalid_request("Malformed X-Slack-Token header", None))?
+                .trim()
+                .to_string(),
+        ),
+        None => None,
+    };
+    let token = header.or_else(|| principal.and_then(|p| p.slack_token.clone()));
+    if let Some(token) = &token {
+        if !token.starts_with("xoxb-") && !token.starts_with("xoxp-") {
+            return Err(ErrorData::invalid_request(
+                "X-Slack-Token must be a bot (xoxb-) or user (xoxp-) token",
+                None,
+            ));
+        }
+    }
+    let owner = Owner {
+        principal: principal.map(|p| p.name.clone()),
+        token: token.as_deref().map(|t| Sha256::digest(t.as_bytes()).into()),
+    };
+    Ok((owner, token))
+}
+
+/// One MCP session: delegates to its own `SlackTools` once bound.
+pub struct Session {
+    sessions: Sessions,
+    bound: OnceCell<Bound>,
+    slot: Option<OwnedSemaphorePermit>,
+}
+
+impl Session {
+    /// The session's tools, after checking the request comes from its owner.
+    fn tools(&self, context: &RequestContext<RoleServer>) -> Result<&SlackTools, ErrorData> {
+        let bound = self
+            .bound
+            .get()
+            .ok_or_else(|| ErrorData::invalid_request("Session is not initialized", None))?;
+        let (owner, _) = identify(context.extensions.get::<Parts>())?;
+        if owner != bound.owner {
+            tracing::warn!("Refused a request for a session owned by another client");
+            return Err(ErrorData::invalid_request(
+                "This session belongs to another client",
+                None,
+            ));
+        }
+        Ok(&bound.tools)
+    }
+}
+
+impl ServerHandler for Session {
+    fn get_info(&self) -> ServerInfo {
+        self.sessions.shared.get_info()
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        if self.slot.is_none() {
+            return Err(ErrorData::invalid_request(self.sessions.full(), None));
+        }
+        self.bound
+            .get_or_try_init(|| self.sessions.open(context.extensions.get::<Parts>()))
+            .await?;
+        self.tools(&context)?.initialize(request, context).await
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.tools(&context)?.call_tool(request, context).await
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        self.tools(&context)?.list_tools(request, context).await
+    }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        self.tools(&context)?.list_resources(request, context).await
+    }
+
+    async fn list_resource_templates(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        self.tools(&context)?
+            .list_resource_templates(request, context)
+            .await
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        self.tools(&context)?.read_resource(request, context).await
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.tools(&context)?.subscribe(request, context).await
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.tools(&context)?.unsubscribe(request, context).await
+    }
+}
+
+/// Middleware for the MCP routes: while every slot is taken, requests that
+/// would open a session get a 503 instead of a failed initialize.
+pub async fn limit(State(sessions): State<Sessions>, request: Request, next: Next) -> Response {
+    // Streamable HTTP names the session in a header, SSE in the query.
+    let opens_session = request.method() != Method::DELETE
+        && !request.headers().contains_key("mcp-session-id")
+        && !request
+            .uri()
+            .query()
+            .is_some_and(|q| q.contains("sessionId="));
+    if opens_session && sessions.is_full() {
+        tracing::warn!("Refused a new session: all {} slots are taken", sessions.max);
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, "30")],
+            "too many sessions",
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::MockSlack;
+    use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+    use rmcp::transport::streamable_http_server::StreamableHttpService;
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn sessions_act_as_their_own_token_and_stay_isolated() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general");
+        let sessions = Sessions::new(SlackTools::new(mock.client(), None), 1);
+        let factory = sessions.clone();
+        let service = StreamableHttpService::new(
+            move || factory.try_session(),
+            Arc::new(LocalSessionManager::default()),
+            Default::default(),
+        );
+        let app = axum::Router::new()
+            .route_service("/mcp", service)
+            .layer(axum::middleware::from_fn_with_state(sessions.clone(), limit));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // Returns the status, the session ID and the JSON-RPC reply, if any.
+        let http = reqwest::Client::new();
+        let rpc = |session: Option<&str>, token: &str, body: Value| {
+            let mut request = http
+                .post(&url)
+                .header("Accept", "application/json, text/event-stream")
+                .header(TOKEN_HEADER, token)
+                .json(&body);
+            if let Some(session) = session {
+                request = request.header("mcp-session-id", session);
+            }
+            async move {
+                let resp = request.send().await.unwrap();
+                let status = resp.status();
+                let session = resp
+                    .headers()
+                    .get("mcp-session-id")
+                    .map(|v| v.to_str().unwrap().to_string());
+                let text = resp.text().await.unwrap();
+                let reply = text
+                    .lines()
+                    .find_map(|l| l.strip_prefix("data: "))
+                    .map(|data| serde_json::from_str::<Value>(data).unwrap());
+                (status, session, reply)
+            }
+        };
+        let initialize = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26", "capabilities": {},
+                "clientInfo": { "name": "test", "version": "1" },
+            },
+        });
+
+        // Slots come back once the session's transport is dropped.
+        let slot_freed = || async {
+            for _ in 0..100 {
+                if !sessions.is_full() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            panic!("the slot was never released");
+        };
+
+        // A session that fails to initialize gives its slot back.
+        let (_, _, reply) = rpc(None, "not-a-token", initialize.clone()).await;
+        assert!(reply.unwrap()["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("X-Slack-Token"));
+        slot_freed().await;
+
+        let (status, session, _) = rpc(None, "xoxb-tenant-a", initialize.clone()).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+        let session = session.unwrap();
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        rpc(Some(&session), "xoxb-tenant-a", initialized).await;
+
+        let post = json!({
+            "jsonrpc": "2.0", "id": 2, "method": "tools/call",
+            "params": { "name": "post_message", "arguments": { "channel": "C001", "text": "hi" } },
+        });
+        let (_, _, reply) = rpc(Some(&session), "xoxb-tenant-a", post.clone()).await;
+        assert!(reply.unwrap()["result"].is_object());
+        assert_eq!(mock.tokens("auth.test"), vec!["xoxb-tenant-a"]);
+        assert_eq!(mock.tokens("chat.postMessage"), vec!["xoxb-tenant-a"]);
+
+        // Someone else presenting the session ID gets nothing from it.
+        let (_, _, reply) = rpc(Some(&session), "xoxb-tenant-b", post).await;
+        assert_eq!(
+            reply.unwrap()["error"]["message"],
+            "This session belongs to another client"
+        );
+        assert_eq!(mock.tokens("chat.postMessage").len(), 1);
+
+        // The only slot is taken.
+        let (status, _, _) = rpc(None, "xoxb-tenant-b", initialize.clone()).await;
+        assert_eq!(status, reqwest::StatusCode::SERVICE_UNAVAILABLE);
+
+        // Until its session closes.
+        http.delete(&url)
+            .header("mcp-session-id", &session)
+            .header(TOKEN_HEADER, "xoxb-tenant-a")
+            .send()
+            .await
+            .unwrap();
+        slot_freed().await;
+        let (status, _, _) = rpc(None, "xoxb-tenant-a", initialize).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+        // Same token, same client: it is not set up and checked again.
+        assert_eq!(mock.tokens("auth.test"), vec!["xoxb-tenant-a"]);
+    }
+}
@@ -0,0 +1,406 @@
+//! Response shaping: trims raw Slack payloads down to what an LLM needs.
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::blocks;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// The full Slack API response.
+    Raw,
+    /// JSON with only the fields that matter (ts, user, text, thread_ts, ...).
+    #[default]
+    Compact,
+    /// Plain text, one line per message/channel/user.
+    Text,
+}
+
+impl OutputMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "raw" => Some(OutputMode::Raw),
+            "compact" => Some(OutputMode::Compact),
+            "text" => Some(OutputMode::Text),
+            _ => None,
+        }
+    }
+}
+
+pub fn render(data: Value, mode: OutputMode) -> String {
+    match mode {
+        OutputMode::Raw => data.to_string(),
+        OutputMode::Compact => compact(data).to_string(),
+        OutputMode::Text => text(&compact(data)),
+    }
+}
+
+/// Copy `keys` from `src` into `dst`, skipping nulls, empty strings and `false`.
+fn pick(dst: &mut Map<String, Value>, src: &Value, keys: &[&str]) {
+    for key in keys {
+        match src.get(*key) {
+            None | Some(Value::Null) | Some(Value::Bool(false)) => {}
+            Some(Value::String(s)) if s.is_empty() => {}
+            Some(v) => {
+                dst.insert(key.to_string(), v.clone());
+            }
+        }
+    }
+}
+
+pub fn compact_message(m: &Value) -> Value {
+    let mut out = Map::new();
+    pick(
+        &mut out,
+        m,
+        &[
+            "ts",
+            "channel",
+            "channel_name",
+            "user",
+            "user_name",
+            "username",
+            "bot_id",
+            "subtype",
+            "text",
+            "reply_count",
+            "permalink",
+        ],
+    );
+    // Bot and app messages often carry their content only in blocks or
+    // attachments; keep it readable without them.
+    if !out.contains_key("text") {
+        let array = |key| m.get(key).and_then(Value::as_array);
+        let text = array("blocks")
+            .map(|b| blocks::fallback_text(b))
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| array("attachments").map(|a| blocks::attachments_fallback(a)))
+            .filter(|t| !t.trim().is_empty());
+        if let Some(text) = text {
+            out.insert("text".to_string(), json!(text));
+        }
+    }
+    // thread_ts on a parent equals its own ts; only keep it on replies.
+    if m.get("thread_ts").is_some() && m.get("thread_ts") != m.get("ts") {
+        pick(&mut out, m, &["thread_ts"]);
+    }
+    if out.contains_key("user") {
+        out.remove("bot_id");
+        out.remove("username");
+    }
+    if m.get("edited").is_some() {
+        out.insert("edited".to_string(), json!(true));
+    }
+    if let Some(reactions) = m.get("reactions").and_then(|r| r.as_array()) {
+        let reactions: Vec<Value> = reactions
+            .iter()
+            .map(|r| json!({ "name": r["name"], "count": r["count"] }))
+            .collect();
+        out.insert("reactions".to_string(), json!(reactions));
+    }
+    if let Some(files) = m.get("files").and_then(|f| f.as_array()) {
+        let files: Vec<Value> = files
+            .iter()
+            .map(|f| json!({ "id": f["id"], "name": f["name"] }))
+            .collect();
+        out.insert("files".to_string(), json!(files));
+    }
+    Value::Object(out)
+}
+
+fn compact_channel(c: &Value) -> Value {
+    let mut out = Map::new();
+    pick(
+        &mut out,
+        c,
+        &[
+            "id",
+            "name",
+            "is_private",
+            "is_im",
+            "is_mpim",
+            "is_archived",
+            "is_member",
+            "num_members",
+            "user",
+        ],
+    );
+    for key in ["topic", "purpose"] {
+        if let Some(v) = c
+            .pointer(&format!("/{}/value", key))
+            .and_then(|v| v.as_str())
+        {
+            if !v.is_empty() {
+                out.insert(key.to_string(), json!(v));
+            }
+        }
+    }
+    Value::Object(out)
+}
+
+fn compact_user(u: &Value) -> Value {
+    let mut out = Map::new();
+    pick(
+        &mut out,
+        u,
+        &["id", "name", "real_name", "is_bot", "deleted", "tz"],
+    );
+    if let Some(display) = u.pointer("/profile/display_name").and_then(|v| v.as_str()) {
+        if !display.is_empty() {
+            out.insert("display_name".to_string(), json!(display));
+        }
+    }
+    if !out.contains_key("real_name") {
+        pick(&mut out, &u["profile"], &["real_name"]);
+    }
+    Value::Object(out)
+}
+
+fn compact_list_item(i: &Value) -> Value {
+    let mut out = Map::new();
+    pick(
+        &mut out,
+        i,
+        &[
+            "id",
+            "list_id",
+            "fields",
+            "created_by",
+            "date_created",
+            "is_archived",
+        ],
+    );
+    Value::Object(out)
+}
+
+fn compact_file(f: &Value) -> Value {
+    let mut out = Map::new();
+    pick(
+        &mut out,
+        f,
+        &[
+            "id",
+            "name",
+            "title",
+            "mimetype",
+            "filetype",
+            "size",
+            "user",
+            "created",
+            "channels",
+            "permalink",
+        ],
+    );
+    Value::Object(out)
+}
+
+/// Flatten a `search.messages` response into the same `messages` shape that
+/// `conversations.history` has, so compact/text rendering treat both alike.
+pub fn search_results(data: Value) -> Value {
+    let messages: Vec<Value> = data
+        .pointer("/messages/matches")
+        .and_then(|m| m.as_array())
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut m| {
+            let channel = m["channel"].take();
+            m["channel"] = channel["id"].clone();
+            m["channel_name"] = channel["name"].clone();
+            m
+        })
+        .collect();
+    let paging = data
+        .pointer("/messages/paging")
+        .cloned()
+        .unwrap_or_default();
+    json!({
+        "messages": messages,
+        "total": paging["total"],
+        "page": paging["page"],
+        "pages": paging["pages"],
+    })
+}
+
+fn map_array(v: &Value, f: fn(&Value) -> Value) -> Value {
+    match v.as_array() {
+        Some(items) => Value::Array(items.iter().map(f).collect()),
+        None => v.clone(),
+    }
+}
+
+/// Reduce a Slack response to the fields that matter. Unknown top-level keys
+/// pass through untouched; `ok` and `response_metadata` are dropped, with the
+/// next cursor lifted to `next_cursor`.
+pub fn compact(data: Value) -> Value {
+    let Value::Object(map) = data else {
+        return data;
+    };
+    let next_cursor = map
+        .get("response_metadata")
+        .and_then(|m| m["next_cursor"].as_str())
+        .filter(|c| !c.is_empty())
+        .map(String::from);
+    let mut out = Map::new();
+    for (key, value) in map {
+        let value = match key.as_str() {
+            "ok" | "warning" | "response_metadata" => continue,
+            "messages" => map_array(&value, compact_message),
+            "message" => compact_message(&value),
+            "channels" => map_array(&value, compact_channel),
+            "channel" if value.is_object() => compact_channel(&value),
+            "members" => map_array(&value, compact_user),
+            "user" if value.is_object() => compact_user(&value),
+            "items" => map_array(&value, compact_list_item),
+            "item" | "record" => compact_list_item(&value),
+            "files" => map_array(&value, compact_file),
+            "file" => compact_file(&value),
+            _ => value,
+        };
+        out.insert(key, value);
+    }
+    if let Some(cursor) = next_cursor {
+        out.insert("next_cursor".to_string(), json!(cursor));
+    }
+    Value::Object(out)
+}
+
+fn message_line(m: &Value) -> String {
+    let who = m["user_name"]
+        .as_str()
+        .or_else(|| m["user"].as_str())
+        .or_else(|| m["username"].as_str())
+        .or_else(|| m["bot_id"].as_str())
+        .unwrap_or("unknown");
+    let mut line = format!("[{}] ", m["ts"].as_str().unwrap_or(""));
+    if let Some(channel) = m["channel_name"].as_str() {
+        line.push_str(&format!("#{} ", channel));
+    }
+    line.push_str(&format!("{}: {}", who, m["text"].as_str().unwrap_or("")));
+    if let Some(n) = m["reply_count"].as_u64() {
+        line.push_str(&format!(" ({} replies)", n));
+    }
+    if let Some(reactions) = m["reactions"].as_array() {
+        for r in reactions {
+            line.push_str(&format!(
+                " :{}: {}",
+                r["name"].as_str().unwrap_or(""),
+                r["count"]
+            ));
+        }
+    }
+    line
+}
+
+fn channel_line(c: &Value) -> String {
+    let mut line = format!(
+        "#{} ({})",
+        c["name"].as_str().unwrap_or(""),
+        c["id"].as_str().unwrap_or("")
+    );
+    if let Some(topic) = c["topic"].as_str() {
+        line.push_str(&format!(" — {}", topic));
+    }
+    line
+}
+
+fn user_line(u: &Value) -> String {
+    let name = u["display_name"]
+        .as_str()
+        .or_else(|| u["name"].as_str())
+        .unwrap_or("");
+    let mut line = format!("@{} ({})", name, u["id"].as_str().unwrap_or(""));
+    if let Some(real) = u["real_name"].as_str() {
+        line.push_str(&format!(" — {}", real));
+    }
+    line
+}
+
+/// Render an already-compacted response as plain text. Falls back to compact
+/// JSON for shapes with no obvious line format (lists, canvases, ...).
+fn text(data: &Value) -> String {
+    let lines: Vec<String> = if let Some(messages) = data["messages"].as_array() {
+        messages.iter().map(message_line).collect()
+    } else if let Some(channels) = data["channels"].as_array() {
+        channels.iter().map(channel_line).collect()
+    } else if let Some(members) = data["members"].as_array() {
+        members.iter().map(user_line).collect()
+    } else if data["message"].is_object() {
+        vec![message_line(&data["message"])]
+    } else {
+        return data.to_string();
+    };
+
+    let mut out = lines.join("\n");
+    if let Some(cursor) = data["next_cursor"].as_str() {
+        out.push_str(&format!("\n(more available, next_cursor: {})", cursor));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_drops_noise() {
+        let raw = json!({
+            "ok": true,
+            "messages": [{
+                "type": "message",
+                "ts": "1.0",
+                "thread_ts": "1.0",
+                "user": "U1",
+                "text": "hi",
+                "team": "T1",
+                "blocks": [{ "type": "rich_text" }],
+                "reply_count": 2,
+                "reactions": [{ "name": "tada", "count": 1, "users": ["U2"] }],
+            }],
+            "response_metadata": { "next_cursor": "abc" },
+        });
+
+        assert_eq!(
+            compact(raw),
+            json!({
+                "messages": [{
+                    "ts": "1.0",
+                    "user": "U1",
+                    "text": "hi",
+                    "reply_count": 2,
+                    "reactions": [{ "name": "tada", "count": 1 }],
+                }],
+                "next_cursor": "abc",
+            })
+        );
+    }
+
+    #[test]
+    fn compact_reads_blocks_only_messages() {
+        let message = json!({
+            "ts": "1.0",
+            "bot_id": "B1",
+            "text": "",
+            "blocks": [{ "type": "section", "text": { "type": "mrkdwn", "text": "*Deploy* done" } }],
+        });
+        assert_eq!(compact_message(&message)["text"], "*Deploy* done");
+
+        let message = json!({
+            "ts": "1.0",
+            "bot_id": "B1",
+            "attachments": [{ "fallback": "Build #12 failed" }],
+        });
+        assert_eq!(compact_message(&message)["text"], "Build #12 failed");
+    }
+
+    #[test]
+    fn text_mode_lists_messages() {
+        let raw = json!({
+            "ok": true,
+            "messages": [{ "ts": "1.0", "user": "U1", "text": "hi", "reply_count": 3 }],
+        });
+        assert_eq!(render(raw, OutputMode::Text), "[1.0] U1: hi (3 replies)");
+    }
+}
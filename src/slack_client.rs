@@ -1,51 +1,840 @@
+use std::time::Duration;
+
 use anyhow::{anyhow, Result};
-use reqwest::Client;
-use serde_json::Value;
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+
+use crate::error::SlackError;
+use crate::files::MAX_DOWNLOAD_BYTES;
+use crate::rate_limit::{RateLimiter, Tier};
+
+const DEFAULT_SLACK_API_BASE: &str = "https://slack.com/api";
+
+/// How many times a single call is retried after HTTP 429 before giving up.
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Used when Slack sends a 429 without a parseable Retry-After header.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// How a Slack Web API method takes its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    Form,
+    Query,
+}
+
+impl Encoding {
+    /// Read methods go over GET, since some of them ignore JSON bodies on some
+    /// workspaces. `files.*` writes only accept form encoding. Everything else
+    /// (chat, canvases, lists) takes JSON.
+    pub fn for_method(method: &str) -> Self {
+        match method {
+            "conversations.history"
+            | "conversations.replies"
+            | "conversations.list"
+            | "conversations.info"
+            | "users.list"
+            | "users.info"
+            | "files.info"
+            | "files.list"
+            | "search.messages" => Encoding::Query,
+            m if m.starts_with("files.") => Encoding::Form,
+            _ => Encoding::Json,
+        }
+    }
+}
+
+/// Flatten a JSON object into form/query pairs the way Slack expects: scalars
+/// as strings, arrays and objects (e.g. `blocks`) as JSON strings.
+fn form_pairs(body: &Value) -> Vec<(String, String)> {
+    let Some(map) = body.as_object() else {
+        return Vec::new();
+    };
+    map.iter()
+        .filter_map(|(k, v)| {
+            let v = match v {
+                Value::Null => return None,
+                Value::String(s) => s.clone(),
+                Value::Bool(_) | Value::Number(_) | Value::Array(_) | Value::Object(_) => {
+                    v.to_string()
+                }
+            };
+            Some((k.clone(), v))
+        })
+        .collect()
+}
+
+/// Which kind of Slack token a call is made with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// `xoxb-`: acts as the app.
+    Bot,
+    /// `xoxp-`: acts as the installing user.
+    User,
+    /// `xapp-`: app-level token, only used to open Socket Mode connections.
+    App,
+}
+
+impl TokenKind {
+    /// Methods Slack only accepts from one kind of token.
+    fn required_for(method: &str) -> Option<Self> {
+        if method.starts_with("search.") || method.starts_with("reminders.") {
+            Some(TokenKind::User)
+        } else if method.starts_with("apps.connections.") {
+            Some(TokenKind::App)
+        } else {
+            None
+        }
+    }
 
-const SLACK_API_BASE: &str = "https://slack.com/api";
+    pub fn env_var(self) -> &'static str {
+        match self {
+            TokenKind::Bot => "SLACK_BOT_TOKEN",
+            TokenKind::User => "SLACK_USER_TOKEN",
+            TokenKind::App => "SLACK_APP_TOKEN",
+        }
+    }
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TokenKind::Bot => "bot",
+            TokenKind::User => "user",
+            TokenKind::App => "app",
+        })
+    }
+}
 
 pub struct SlackClient {
     client: Client,
-    token: String,
+    bot_token: Option<String>,
+    user_token: Option<String>,
+    app_token: Option<String>,
+    base_url: String,
+    limiter: RateLimiter,
 }
 
 impl SlackClient {
-    pub fn from_env() -> Result<Self> {
-        let token = std::env::var("SLACK_TOKEN")
-            .or_else(|_| std::env::var("SLACK_BOT_TOKEN"))
-            .map_err(|_| anyhow!("SLACK_TOKEN environment variable is not set"))?;
-        Ok(Self {
+    pub fn new(
+        bot_token: Option<String>,
+        user_token: Option<String>,
+        base_url: impl Into<String>,
+    ) -> Self {
+        Self {
             client: Client::new(),
-            token,
-        })
+            bot_token,
+            user_token,
+            app_token: None,
+            base_url: base_url.into(),
+            limiter: RateLimiter::new(),
+        }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|s| !s.is_empty());
+        let mut bot_token = var("SLACK_BOT_TOKEN");
+        let mut user_token = var("SLACK_USER_TOKEN");
+        // SLACK_TOKEN predates the split; file it by its prefix.
+        if let Some(token) = var("SLACK_TOKEN") {
+            if token.starts_with("xoxp-") {
+                user_token.get_or_insert(token);
+            } else {
+                bot_token.get_or_insert(token);
+            }
+        }
+        if bot_token.is_none() && user_token.is_none() {
+            return Err(anyhow!(
+                "No Slack token set. Set SLACK_BOT_TOKEN and/or SLACK_USER_TOKEN (SLACK_TOKEN is also accepted)"
+            ));
+        }
+        // Overridable for Enterprise Grid proxies and local stand-ins.
+        let base_url = var("SLACK_API_BASE").unwrap_or_else(|| DEFAULT_SLACK_API_BASE.to_string());
+        let app_token = var("SLACK_APP_TOKEN");
+        if let Some(token) = &app_token {
+            if !token.starts_with("xapp-") {
+                return Err(anyhow!(
+                    "SLACK_APP_TOKEN must be an app-level token (xapp-...)"
+                ));
+            }
+        }
+        Ok(Self::new(
+            bot_token,
+            user_token,
+            base_url.trim_end_matches('/'),
+        )
+        .with_app_token(app_token))
+    }
+
+    /// A separate client for another Slack identity at the same API base:
+    /// `token` is filed as the bot or user token by its prefix, and gets its
+    /// own rate limits.
+    pub fn for_token(&self, token: String) -> Self {
+        if token.starts_with("xoxp-") {
+            Self::new(None, Some(token), self.base_url.clone())
+        } else {
+            Self::new(Some(token), None, self.base_url.clone())
+        }
     }
 
-    pub async fn post(&self, method: &str, body: Value) -> Result<Value> {
-        let url = format!("{}/{}", SLACK_API_BASE, method);
+    /// Set the app-level token Socket Mode connects with.
+    pub fn with_app_token(mut self, app_token: Option<String>) -> Self {
+        self.app_token = app_token;
+        self
+    }
+
+    pub fn has_token(&self, kind: TokenKind) -> bool {
+        match kind {
+            TokenKind::Bot => self.bot_token.is_some(),
+            TokenKind::User => self.user_token.is_some(),
+            TokenKind::App => self.app_token.is_some(),
+        }
+    }
+
+    /// The token to send for `method`: `kind` if given, else the one the method
+    /// requires, else the bot token falling back to the user token.
+    fn token_for(&self, method: &str, kind: Option<TokenKind>) -> Result<&str, SlackError> {
+        let missing = |kind| SlackError::MissingToken {
+            method: method.to_string(),
+            kind,
+        };
+        match kind.or_else(|| TokenKind::required_for(method)) {
+            Some(TokenKind::Bot) => self
+                .bot_token
+                .as_deref()
+                .ok_or_else(|| missing(TokenKind::Bot)),
+            Some(TokenKind::User) => self
+                .user_token
+                .as_deref()
+                .ok_or_else(|| missing(TokenKind::User)),
+            Some(TokenKind::App) => self
+                .app_token
+                .as_deref()
+                .ok_or_else(|| missing(TokenKind::App)),
+            None => self
+                .bot_token
+                .as_deref()
+                .or(self.user_token.as_deref())
+                .ok_or_else(|| missing(TokenKind::Bot)),
+        }
+    }
+
+    /// Like `call`, but with an explicit token choice (`None` = default routing).
+    pub async fn call_with(
+        &self,
+        token: Option<TokenKind>,
+        method: &str,
+        body: Value,
+    ) -> Result<Value, SlackError> {
+        self.send(method, body, Encoding::for_method(method), token)
+            .await
+    }
+
+    /// Like `post`, but with an explicit token choice (`None` = default routing).
+    pub async fn post_with(
+        &self,
+        token: Option<TokenKind>,
+        method: &str,
+        body: Value,
+    ) -> Result<Value, SlackError> {
+        self.send(method, body, Encoding::Json, token).await
+    }
+
+    /// Call `method` using the encoding Slack expects for it (see `Encoding::for_method`).
+    pub async fn call(&self, method: &str, body: Value) -> Result<Value, SlackError> {
+        match Encoding::for_method(method) {
+            Encoding::Json => self.post(method, body).await,
+            Encoding::Form => self.post_form(method, body).await,
+            Encoding::Query => self.get(method, body).await,
+        }
+    }
+
+    /// POST with a JSON body.
+    pub async fn post(&self, method: &str, body: Value) -> Result<Value, SlackError> {
+        self.send(method, body, Encoding::Json, None).await
+    }
+
+    /// POST with an `application/x-www-form-urlencoded` body.
+    pub async fn post_form(&self, method: &str, body: Value) -> Result<Value, SlackError> {
+        self.send(method, body, Encoding::Form, None).await
+    }
+
+    /// GET with arguments in the query string.
+    pub async fn get(&self, method: &str, body: Value) -> Result<Value, SlackError> {
+        self.send(method, body, Encoding::Query, None).await
+    }
+
+    /// Send file contents to an upload URL from `files.getUploadURLExternal`.
+    /// The URL is pre-signed, so no token goes with it.
+    pub async fn upload(&self, url: &str, bytes: Vec<u8>) -> Result<(), SlackError> {
+        let method = "files.upload";
         let resp = self
             .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Content-Type", "application/json; charset=utf-8")
-            .json(&body)
+            .post(url)
+            .header("Content-Type", "application/octet-stream")
+            .body(bytes)
             .send()
-            .await?;
+            .await
+            .map_err(|source| SlackError::Transport {
+                method: method.to_string(),
+                source,
+            })?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(SlackError::Http {
+                method: method.to_string(),
+                status: status.as_u16(),
+                body: resp.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
 
+    /// Whether `url` points at Slack (or at the SLACK_API_BASE host), and so
+    /// may be sent a token.
+    fn is_slack_url(&self, url: &str) -> bool {
+        let host = |u: &str| {
+            reqwest::Url::parse(u)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+        };
+        match host(url) {
+            Some(h) => {
+                h == "slack.com" || h.ends_with(".slack.com") || host(&self.base_url) == Some(h)
+            }
+            None => false,
+        }
+    }
+
+    /// Fetch a private file URL (`url_private`/`url_private_download`) with the
+    /// bearer token. Returns the bytes and the Content-Type Slack served.
+    /// Files over `MAX_DOWNLOAD_BYTES` are refused.
+    pub async fn download(
+        &self,
+        token: Option<TokenKind>,
+        url: &str,
+    ) -> Result<(Vec<u8>, Option<String>), SlackError> {
+        self.download_at_most(token, url, MAX_DOWNLOAD_BYTES).await
+    }
+
+    async fn download_at_most(
+        &self,
+        token: Option<TokenKind>,
+        url: &str,
+        max: u64,
+    ) -> Result<(Vec<u8>, Option<String>), SlackError> {
+        let method = "files.download";
+        if !self.is_slack_url(url) {
+            return Err(SlackError::Api {
+                method: method.to_string(),
+                error: format!("refusing to send the Slack token to {}", url),
+            });
+        }
+        let token = self.token_for(method, token)?;
+        let transport = |source| SlackError::Transport {
+            method: method.to_string(),
+            source,
+        };
+        let mut resp = self
+            .client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .map_err(transport)?;
         let status = resp.status();
-        let data: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(SlackError::Http {
+                method: method.to_string(),
+                status: status.as_u16(),
+                body: resp.text().await.unwrap_or_default(),
+            });
+        }
+        let content_type = resp
+            .headers()
+            .get("Content-Type")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let too_large = || SlackError::Api {
+            method: method.to_string(),
+            error: format!("file is larger than {} bytes", max),
+        };
+        if resp.content_length().is_some_and(|len| len > max) {
+            return Err(too_large());
+        }
+        // The length header may be missing or wrong; count what arrives.
+        let mut bytes = Vec::new();
+        while let Some(chunk) = resp.chunk().await.map_err(transport)? {
+            if (bytes.len() + chunk.len()) as u64 > max {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok((bytes, content_type))
+    }
 
+    async fn send(
+        &self,
+        method: &str,
+        body: Value,
+        encoding: Encoding,
+        token: Option<TokenKind>,
+    ) -> Result<Value, SlackError> {
+        let url = format!("{}/{}", self.base_url, method);
+        let token = self.token_for(method, token)?;
+        let tier = Tier::for_method(method);
+        let key = RateLimiter::key(method, &body);
+
+        let mut retries = 0;
+        let resp = loop {
+            self.limiter.acquire(&key, tier).await;
+
+            let req = match encoding {
+                Encoding::Json => self
+                    .client
+                    .post(&url)
+                    .header("Content-Type", "application/json; charset=utf-8")
+                    .json(&body),
+                Encoding::Form => self.client.post(&url).form(&form_pairs(&body)),
+                Encoding::Query => self.client.get(&url).query(&form_pairs(&body)),
+            };
+
+            let resp = req
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .map_err(|source| SlackError::Transport {
+                    method: method.to_string(),
+                    source,
+                })?;
+
+            if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+                break resp;
+            }
+
+            let retry_after = resp
+                .headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_RETRY_AFTER);
+
+            if retries >= MAX_RATE_LIMIT_RETRIES {
+                return Err(SlackError::RateLimited {
+                    method: method.to_string(),
+                    retry_after: retry_after.as_secs(),
+                });
+            }
+            retries += 1;
+
+            tracing::warn!(
+                "Slack rate limited {} (attempt {}), retrying in {}s",
+                method,
+                retries,
+                retry_after.as_secs()
+            );
+            self.limiter.penalize(&key, retry_after).await;
+        };
+
+        let status = resp.status();
         if !status.is_success() {
-            return Err(anyhow!("Slack API HTTP error {}: {}", status, data));
+            return Err(SlackError::Http {
+                method: method.to_string(),
+                status: status.as_u16(),
+                body: resp.text().await.unwrap_or_default(),
+            });
         }
 
+        let data: Value = resp.json().await.map_err(|source| SlackError::Transport {
+            method: method.to_string(),
+            source,
+        })?;
+
         if data.get("ok") != Some(&Value::Bool(true)) {
-            let error = data
-                .get("error")
-                .and_then(|e| e.as_str())
-                .unwrap_or("unknown");
-            return Err(anyhow!("Slack API error in {}: {} ({})", method, error, data));
+            return Err(SlackError::from_response(method, &body, &data));
         }
 
         Ok(data)
     }
+
+    /// Follow `response_metadata.next_cursor` for a cursor-paginated method,
+    /// collecting the `items_key` array from each page until Slack runs out of
+    /// pages or one of `limits` is hit.
+    ///
+    /// Page sizes are clamped so the item cap is never overshot, and a page
+    /// that would blow the token budget is dropped rather than cut, so
+    /// `next_cursor` always resumes exactly after the last returned item.
+    pub async fn paginate(
+        &self,
+        method: &str,
+        mut body: Value,
+        items_key: &str,
+        limits: PageLimits,
+    ) -> Result<Paginated, SlackError> {
+        let page_size = body
+            .get("limit")
+            .and_then(|l| l.as_u64())
+            .unwrap_or(DEFAULT_PAGE_SIZE as u64) as usize;
+        let mut cursor = body
+            .get("cursor")
+            .and_then(|c| c.as_str())
+            .filter(|c| !c.is_empty())
+            .map(String::from);
+
+        let mut items = Vec::new();
+        let mut tokens = 0;
+        let mut pages = 0;
+
+        loop {
+            let remaining = limits.max_items.map(|max| max.saturating_sub(items.len()));
+            if remaining == Some(0) || pages >= MAX_PAGES {
+                break;
+            }
+            body["limit"] = json!(remaining.map_or(page_size, |r| r.min(page_size)));
+            match &cursor {
+                Some(c) => body["cursor"] = json!(c),
+                None => {
+                    if let Some(map) = body.as_object_mut() {
+                        map.remove("cursor");
+                    }
+                }
+            }
+
+            let data = self.call(method, body.clone()).await?;
+            pages += 1;
+
+            let page = data
+                .get(items_key)
+                .and_then(|i| i.as_array())
+                .cloned()
+                .unwrap_or_default();
+            let page_tokens = estimate_tokens(&page);
+            if let Some(max_tokens) = limits.max_tokens {
+                if !items.is_empty() && tokens + page_tokens > max_tokens {
+                    break;
+                }
+            }
+            tokens += page_tokens;
+            items.extend(page);
+
+            cursor = data
+                .pointer("/response_metadata/next_cursor")
+                .and_then(|c| c.as_str())
+                .filter(|c| !c.is_empty())
+                .map(String::from);
+            if cursor.is_none() || limits.max_tokens.is_some_and(|max| tokens >= max) {
+                break;
+            }
+        }
+
+        Ok(Paginated {
+            items,
+            truncated: cursor.is_some(),
+            next_cursor: cursor,
+            pages,
+        })
+    }
+}
+
+/// Page size used while auto-paginating when the caller gave no `limit`.
+const DEFAULT_PAGE_SIZE: usize = 200;
+
+/// Hard stop so a runaway "fetch all" cannot page forever.
+const MAX_PAGES: u32 = 100;
+
+/// Stopping conditions for `SlackClient::paginate`. `None` means unbounded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PageLimits {
+    pub max_items: Option<usize>,
+    /// Rough budget for the returned items, in LLM tokens (~4 bytes of JSON each).
+    pub max_tokens: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct Paginated {
+    pub items: Vec<Value>,
+    /// True if Slack had more items than were returned.
+    pub truncated: bool,
+    /// Cursor to resume from when `truncated`.
+    pub next_cursor: Option<String>,
+    pub pages: u32,
+}
+
+impl Paginated {
+    pub fn to_json(&self, items_key: &str) -> Value {
+        json!({
+            items_key: self.items,
+            "truncated": self.truncated,
+            "next_cursor": self.next_cursor,
+            "pages": self.pages,
+        })
+    }
+}
+
+fn estimate_tokens(items: &[Value]) -> usize {
+    items.iter().map(|i| i.to_string().len()).sum::<usize>() / 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::{Failure, MockSlack};
+
+    #[tokio::test]
+    async fn retries_after_429() {
+        let mock = MockSlack::start().await;
+        mock.fail_next("users.list", Failure::RateLimited { retry_after: 1 });
+        let client = mock.client();
+
+        let started = std::time::Instant::now();
+        let data = client.post("users.list", json!({})).await.unwrap();
+
+        assert_eq!(data["ok"], json!(true));
+        assert_eq!(mock.calls("users.list").len(), 2);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let mock = MockSlack::start().await;
+        for _ in 0..=MAX_RATE_LIMIT_RETRIES {
+            mock.fail_next("users.list", Failure::RateLimited { retry_after: 0 });
+        }
+
+        let err = mock
+            .client()
+            .post("users.list", json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SlackError::RateLimited { retry_after: 0, .. }
+        ));
+        assert_eq!(
+            mock.calls("users.list").len() as u32,
+            MAX_RATE_LIMIT_RETRIES + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn post_message_is_limited_per_channel() {
+        let limiter = RateLimiter::new();
+        let a = RateLimiter::key("chat.postMessage", &json!({ "channel": "C1" }));
+        let b = RateLimiter::key("chat.postMessage", &json!({ "channel": "C2" }));
+
+        let started = tokio::time::Instant::now();
+        limiter.acquire(&a, Tier::PostMessage).await;
+        limiter.acquire(&b, Tier::PostMessage).await;
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        limiter.acquire(&a, Tier::PostMessage).await;
+        assert!(started.elapsed() >= Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn classifies_slack_errors() {
+        let mock = MockSlack::start().await;
+        mock.fail_next("users.list", Failure::Error("invalid_auth"));
+        mock.fail_next("lists.update", Failure::Error("no_permission"));
+        mock.fail_next("conversations.history", Failure::Error("not_in_channel"));
+        mock.fail_next(
+            "chat.postMessage",
+            Failure::Body(json!({
+                "ok": false,
+                "error": "missing_scope",
+                "needed": "chat:write",
+                "provided": "channels:read,users:read",
+            })),
+        );
+        let client = mock.client();
+
+        let err = client.post("users.list", json!({})).await.unwrap_err();
+        assert!(matches!(err, SlackError::InvalidAuth { .. }));
+        let err = client.post("lists.update", json!({})).await.unwrap_err();
+        assert!(matches!(err, SlackError::NoPermission { .. }));
+
+        let err = client
+            .post("conversations.history", json!({ "channel": "C001" }))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SlackError::NotInChannel { channel: Some(ref c), .. } if c == "C001")
+        );
+
+        let err = client
+            .post(
+                "chat.postMessage",
+                json!({ "channel": "C001", "text": "hi" }),
+            )
+            .await
+            .unwrap_err();
+        match err {
+            SlackError::MissingScope {
+                needed, provided, ..
+            } => {
+                assert_eq!(needed, ["chat:write"]);
+                assert_eq!(provided, ["channels:read", "users:read"]);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn encodes_per_method() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general");
+        let client = mock.client();
+
+        client
+            .call(
+                "conversations.history",
+                json!({ "channel": "C001", "limit": 5 }),
+            )
+            .await
+            .unwrap();
+        client
+            .post_form(
+                "chat.postMessage",
+                json!({ "channel": "C001", "text": "hi" }),
+            )
+            .await
+            .unwrap();
+        client
+            .call(
+                "chat.postMessage",
+                json!({ "channel": "C001", "text": "hi" }),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(mock.encodings("conversations.history"), ["query"]);
+        assert_eq!(mock.calls("conversations.history")[0]["limit"], "5");
+        assert_eq!(mock.encodings("chat.postMessage"), ["form", "json"]);
+    }
+
+    #[test]
+    fn form_pairs_stringify_nested_values() {
+        let pairs = form_pairs(&json!({
+            "channel": "C001",
+            "limit": 10,
+            "blocks": [{ "type": "divider" }],
+            "cursor": null,
+        }));
+        assert!(pairs.contains(&("limit".to_string(), "10".to_string())));
+        assert!(pairs.contains(&("blocks".to_string(), r#"[{"type":"divider"}]"#.to_string())));
+        assert!(!pairs.iter().any(|(k, _)| k == "cursor"));
+    }
+
+    #[tokio::test]
+    async fn paginate_stops_at_item_cap() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general");
+        for i in 0..5 {
+            mock.add_message("C001", "U001", &format!("m{}", i));
+        }
+        let client = mock.client();
+        let body = json!({ "channel": "C001", "limit": 2 });
+
+        let capped = client
+            .paginate(
+                "conversations.history",
+                body.clone(),
+                "messages",
+                PageLimits {
+                    max_items: Some(3),
+                    max_tokens: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(capped.items.len(), 3);
+        assert_eq!(capped.pages, 2);
+        assert!(capped.truncated);
+        assert_eq!(capped.next_cursor.as_deref(), Some("3"));
+
+        let all = client
+            .paginate(
+                "conversations.history",
+                body,
+                "messages",
+                PageLimits::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(all.items.len(), 5);
+        assert!(!all.truncated);
+        assert_eq!(all.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn paginate_respects_token_budget() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general");
+        for _ in 0..4 {
+            mock.add_message("C001", "U001", &"x".repeat(400));
+        }
+
+        let page = mock
+            .client()
+            .paginate(
+                "conversations.history",
+                json!({ "channel": "C001", "limit": 1 }),
+                "messages",
+                PageLimits {
+                    max_items: None,
+                    max_tokens: Some(250),
+                },
+            )
+            .await
+            .unwrap();
+
+        // Each message is a little over 100 tokens, so only two fit.
+        assert_eq!(page.items.len(), 2);
+        assert!(page.truncated);
+        assert_eq!(page.next_cursor.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn http_errors_keep_status() {
+        let mock = MockSlack::start().await;
+        mock.fail_next("users.list", Failure::Status(503));
+
+        let err = mock
+            .client()
+            .post("users.list", json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SlackError::Http { status: 503, .. }));
+    }
+
+    #[tokio::test]
+    async fn downloads_stop_at_the_cap() {
+        use axum::body::Body;
+        use axum::routing::get;
+
+        // One body announces its length, the other is streamed without one.
+        let app = axum::Router::new()
+            .route("/sized", get(|| async { vec![0u8; 100] }))
+            .route(
+                "/streamed",
+                get(|| async {
+                    let chunks = (0..10).map(|_| Ok::<_, std::io::Error>(vec![0u8; 10]));
+                    Body::from_stream(futures_util::stream::iter(chunks))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = SlackClient::new(Some("xoxb-test".to_string()), None, base.clone());
+
+        for path in ["sized", "streamed"] {
+            let url = format!("{}/{}", base, path);
+            let err = client.download_at_most(None, &url, 50).await.unwrap_err();
+            assert!(err.to_string().contains("larger than 50 bytes"), "{}", err);
+            let (bytes, _) = client.download_at_most(None, &url, 100).await.unwrap();
+            assert_eq!(bytes.len(), 100);
+        }
+    }
 }
@@ -0,0 +1,226 @@
+//! Socket Mode: receive Slack events and interactions over a WebSocket
+//! opened with the app-level token, so the server sees activity without a
+//! public URL.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::events::{EventBus, SlackEvent};
+use crate::slack_client::SlackClient;
+
+/// Slack pings every ~30s; this long without any frame means the link is dead.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Reconnect delays double from the first to the last after failures.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+pub struct SocketMode {
+    client: Arc<SlackClient>,
+    bus: EventBus,
+}
+
+impl SocketMode {
+    pub fn new(client: Arc<SlackClient>, bus: EventBus) -> Self {
+        Self { client, bus }
+    }
+
+    /// Stay connected until the task is dropped, reconnecting whenever Slack
+    /// asks to or the connection fails.
+    pub async fn run(self) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            match self.connect().await {
+                Ok(()) => {
+                    tracing::info!("Socket Mode connection closed, reconnecting");
+                    // Still pause, so a socket that closes at once can't spin.
+                    tokio::time::sleep(MIN_BACKOFF).await;
+                    backoff = MIN_BACKOFF;
+                    continue;
+                }
+                Err(e) => tracing::warn!("Socket Mode connection failed: {:#}", e),
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Open one connection and serve it until it ends. `Ok` means it ended
+    /// normally (Slack asked for a reconnect or closed the socket).
+    async fn connect(&self) -> Result<()> {
+        let data = self
+            .client
+            .call("apps.connections.open", json!({}))
+            .await?;
+        let url = data["url"]
+            .as_str()
+            .ok_or_else(|| anyhow!("apps.connections.open returned no url"))?;
+        self.session(url).await
+    }
+
+    async fn session(&self, url: &str) -> Result<()> {
+        let (mut ws, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .context("WebSocket handshake failed")?;
+
+        loop {
+            let frame = tokio::time::timeout(IDLE_TIMEOUT, ws.next())
+                .await
+                .map_err(|_| anyhow!("no frames from Slack for {:?}", IDLE_TIMEOUT))?;
+            let text = match frame {
+                None | Some(Ok(Message::Close(_))) => return Ok(()),
+                Some(Err(e)) => return Err(e.into()),
+                Some(Ok(Message::Text(text))) => text,
+                // Pings are answered by tungstenite on the next read.
+                Some(Ok(_)) => continue,
+            };
+            let envelope: Value = match serde_json::from_str(&text) {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::warn!("Ignoring unreadable Socket Mode frame: {}", e);
+                    continue;
+                }
+            };
+
+            // Ack first: Slack redelivers envelopes not acked within 3 seconds.
+            if let Some(id) = envelope["envelope_id"].as_str() {
+                let ack = json!({ "envelope_id": id }).to_string();
+                ws.send(Message::text(ack)).await?;
+            }
+
+            match envelope["type"].as_str().unwrap_or_default() {
+                "hello" => tracing::info!("Socket Mode connected"),
+                "disconnect" => {
+                    tracing::info!(
+                        "Slack asked to reconnect Socket Mode ({})",
+                        envelope["reason"].as_str().unwrap_or("unknown")
+                    );
+                    let _ = ws.close(None).await;
+                    return Ok(());
+                }
+                "events_api" => {
+                    if let Some(event) = SlackEvent::from_event(&envelope["payload"]["event"]) {
+                        self.bus.publish(event);
+                    }
+                }
+                "interactive" => {
+                    if let Some(event) = SlackEvent::from_interaction(&envelope["payload"]) {
+                        self.bus.publish(event);
+                    }
+                }
+                other => tracing::debug!("Ignoring Socket Mode envelope of type {}", other),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::MockSlack;
+    use tokio::net::TcpListener;
+
+    /// A one-connection stand-in for Slack's Socket Mode endpoint: sends
+    /// `frames` in order and returns what the client sent back.
+    async fn socket_server(frames: Vec<Value>) -> (String, tokio::task::JoinHandle<Vec<Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/link", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut received = Vec::new();
+            for frame in frames {
+                let expects_ack = frame.get("envelope_id").is_some();
+                ws.send(Message::text(frame.to_string())).await.unwrap();
+                if expects_ack {
+                    match ws.next().await {
+                        Some(Ok(Message::Text(text))) => {
+                            received.push(serde_json::from_str(&text).unwrap())
+                        }
+                        other => panic!("expected an ack, got {:?}", other),
+                    }
+                }
+            }
+            // Drain until the client closes.
+            while let Some(Ok(msg)) = ws.next().await {
+                if msg.is_close() {
+                    break;
+                }
+            }
+            received
+        });
+        (url, handle)
+    }
+
+    fn envelope(id: &str, event: Value) -> Value {
+        json!({
+            "envelope_id": id,
+            "type": "events_api",
+            "accepts_response_payload": false,
+            "payload": { "type": "event_callback", "event": event },
+        })
+    }
+
+    #[tokio::test]
+    async fn acks_envelopes_and_publishes_events() {
+        let mock = MockSlack::start().await;
+        let (url, server) = socket_server(vec![
+            json!({ "type": "hello", "num_connections": 1 }),
+            envelope(
+                "e1",
+                json!({ "type": "message", "channel": "C001", "ts": "1.0", "text": "hi" }),
+            ),
+            envelope(
+                "e2",
+                json!({ "type": "app_mention", "channel": "C001", "ts": "2.0", "text": "<@UBOT>" }),
+            ),
+            envelope("e3", json!({ "type": "team_join" })),
+            json!({
+                "envelope_id": "e4",
+                "type": "interactive",
+                "payload": { "type": "block_actions", "actions": [] },
+            }),
+            json!({ "type": "disconnect", "reason": "refresh_requested" }),
+        ])
+        .await;
+        mock.set_socket_url(&url);
+
+        let bus = EventBus::new();
+        let mut rx = bus.subscribe();
+        let socket = SocketMode::new(Arc::new(mock.client()), bus);
+        socket.connect().await.unwrap();
+
+        let acks = server.await.unwrap();
+        assert_eq!(
+            acks,
+            vec![
+                json!({ "envelope_id": "e1" }),
+                json!({ "envelope_id": "e2" }),
+                json!({ "envelope_id": "e3" }),
+                json!({ "envelope_id": "e4" }),
+            ]
+        );
+        assert!(matches!(rx.recv().await.unwrap(), SlackEvent::Message(_)));
+        assert!(matches!(rx.recv().await.unwrap(), SlackEvent::AppMention(_)));
+        assert!(matches!(rx.recv().await.unwrap(), SlackEvent::Interaction(_)));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(mock.tokens("apps.connections.open"), vec!["xapp-test-token"]);
+    }
+
+    #[tokio::test]
+    async fn dropped_connections_are_reported() {
+        let mock = MockSlack::start().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        mock.set_socket_url(&format!("ws://{}/", listener.local_addr().unwrap()));
+        // Accept the TCP connection, then hang up mid-handshake.
+        tokio::spawn(async move { drop(listener.accept().await.unwrap()) });
+
+        let socket = SocketMode::new(Arc::new(mock.client()), EventBus::new());
+        assert!(socket.connect().await.is_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! resources/subscribe: watch subscribed channels and threads for new
+//! messages and tell the client with notifications/resources/updated.
+//!
+//! Messages arrive on the event bus when Socket Mode or the Events API
+//! endpoint is enabled, with a slow poll to catch any the bus missed;
+//! otherwise the subscribed conversations are polled.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rmcp::model::{ErrorData, ResourceUpdatedNotificationParam};
+use rmcp::service::Peer;
+use rmcp::RoleServer;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use crate::error::SlackError;
+use crate::events::{ts_key, EventBus, SlackEvent};
+use crate::resources::SlackUri;
+use crate::slack_client::{PageLimits, SlackClient};
+
+/// How often subscribed conversations are polled unless configured otherwise.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Least time between polls that reconcile a subscription fed by events.
+pub const RECONCILE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+struct Watch {
+    target: SlackUri,
+    /// ts of the newest message seen; only later messages count as updates.
+    oldest: String,
+}
+
+/// The subscriptions of one MCP session.
+pub struct Subscriptions {
+    watches: Mutex<HashMap<String, Watch>>,
+    peer: Mutex<Option<Peer<RoleServer>>>,
+    poller: Mutex<Option<JoinHandle<()>>>,
+    interval: Duration,
+    events: Option<EventBus>,
+}
+
+/// The newest ts in `messages` that is later than `oldest`.
+fn newest_after(messages: &[Value], oldest: Option<&str>) -> Option<String> {
+    messages
+        .iter()
+        .filter_map(|m| m["ts"].as_str())
+        .filter(|ts| oldest.is_none_or(|o| ts_key(ts) > ts_key(o)))
+        .max_by_key(|ts| ts_key(ts))
+        .map(String::from)
+}
+
+/// The newest message in a channel or thread later than `oldest`, if any.
+async fn newest(
+    client: &SlackClient,
+    target: &SlackUri,
+    oldest: Option<&str>,
+) -> Result<Option<String>, SlackError> {
+    let messages = match target {
+        SlackUri::Channel(channel) => {
+            let mut body = json!({ "channel": channel, "limit": 100 });
+            if let Some(oldest) = oldest {
+                body["oldest"] = json!(oldest);
+            }
+            let data = client.call("conversations.history", body).await?;
+            data["messages"].as_array().cloned().unwrap_or_default()
+        }
+        SlackUri::Thread { channel, ts } => {
+            let mut body = json!({ "channel": channel, "ts": ts });
+            if let Some(oldest) = oldest {
+                body["oldest"] = json!(oldest);
+            }
+            client
+                .paginate("conversations.replies", body, "messages", PageLimits::default())
+                .await?
+                .items
+        }
+        _ => Vec::new(),
+    };
+    // Slack always returns a thread's parent, so filter here as well.
+    Ok(newest_after(&messages, oldest))
+}
+
+impl Subscriptions {
+    /// Poll every `interval`, or follow `events` when there is a bus and
+    /// poll every `RECONCILE_INTERVAL` at the most.
+    pub fn new(interval: Duration, events: Option<EventBus>) -> Self {
+        Self {
+            watches: Mutex::new(HashMap::new()),
+            peer: Mutex::new(None),
+            poller: Mutex::new(None),
+            interval,
+            events,
+        }
+    }
+
+    /// Start watching `uri` for `peer`. Subscribing again is a no-op.
+    pub async fn subscribe(
+        self: &Arc<Self>,
+        client: &Arc<SlackClient>,
+        uri: &str,
+        peer: Peer<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let target = match SlackUri::parse(uri) {
+            Some(t @ (SlackUri::Channel(_) | SlackUri::Thread { .. })) => t,
+            Some(_) => {
+                return Err(ErrorData::invalid_params(
+                    format!("{} does not support subscriptions", uri),
+                    Some(json!({
+                        "hint": "Only slack://channel/{id} and slack://thread/{channel}/{ts} \
+                                 can be subscribed to.",
+                    })),
+                ))
+            }
+            None => {
+                return Err(ErrorData::resource_not_found(
+                    format!("Unknown resource URI: {}", uri),
+                    None,
+                ))
+            }
+        };
+        *self.peer.lock().unwrap() = Some(peer);
+        if self.watches.lock().unwrap().contains_key(uri) {
+            return Ok(());
+        }
+
+        let oldest = newest(client, &target, None)
+            .await?
+            .unwrap_or_else(|| "0".to_string());
+        self.watches
+            .lock()
+            .unwrap()
+            .entry(uri.to_string())
+            .or_insert(Watch { target, oldest });
+
+        let mut poller = self.poller.lock().unwrap();
+        if poller.as_ref().is_none_or(|p| p.is_finished()) {
+            *poller = Some(tokio::spawn(Arc::clone(self).run(Arc::clone(client))));
+        }
+        Ok(())
+    }
+
+    pub fn unsubscribe(&self, uri: &str) {
+        self.watches.lock().unwrap().remove(uri);
+    }
+
+    /// Poll every watched conversation once. Returns the URIs that have new
+    /// messages and moves their watermarks forward.
+    pub async fn check(&self, client: &SlackClient) -> Vec<String> {
+        let targets: Vec<(String, SlackUri, String)> = self
+            .watches
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(uri, w)| (uri.clone(), w.target.clone(), w.oldest.clone()))
+            .collect();
+
+        let mut updated = Vec::new();
+        for (uri, target, oldest) in targets {
+            match newest(client, &target, Some(&oldest)).await {
+                Ok(Some(ts)) => {
+                    if let Some(watch) = self.watches.lock().unwrap().get_mut(&uri) {
+                        watch.oldest = ts;
+                        updated.push(uri);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Polling {} failed: {}", uri, e),
+            }
+        }
+        updated
+    }
+
+    /// The watched URIs a new message event touches, with their watermarks
+    /// moved forward.
+    fn matching(&self, event: &SlackEvent) -> Vec<String> {
+        let (SlackEvent::Message(_), Some(channel), Some(ts)) =
+            (event, event.channel(), event.ts())
+        else {
+            return Vec::new();
+        };
+        let mut uris = Vec::new();
+        match event.thread_ts() {
+            Some(thread_ts) => {
+                uris.push(SlackUri::Thread {
+                    channel: channel.to_string(),
+                    ts: thread_ts.to_string(),
+                });
+                // Thread parents and broadcast replies also show in the channel.
+                if thread_ts == ts || event.payload()["subtype"] == "thread_broadcast" {
+                    uris.push(SlackUri::Channel(channel.to_string()));
+                }
+            }
+            None => uris.push(SlackUri::Channel(channel.to_string())),
+        }
+
+        let mut watches = self.watches.lock().unwrap();
+        uris.into_iter()
+            .map(|u| u.to_string())
+            .filter(|uri| match watches.get_mut(uri) {
+                Some(watch) => {
+                    if ts_key(ts) > ts_key(&watch.oldest) {
+                        watch.oldest = ts.to_string();
+                    }
+                    true
+                }
+                None => false,
+            })
+            .collect()
+    }
+
+    /// Watch until the session goes away or nothing is subscribed.
+    async fn run(self: Arc<Self>, client: Arc<SlackClient>) {
+        let mut events = self.events.as_ref().map(|bus| bus.subscribe());
+        let every = |period| {
+            let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker
+        };
+        let mut ticker = match events {
+            Some(_) => every(self.interval.max(RECONCILE_INTERVAL)),
+            None => every(self.interval),
+        };
+        loop {
+            let updated = match events.as_mut() {
+                // The slow poll catches events the bus never delivered, and
+                // notices unsubscribes and closed sessions while it is quiet.
+                Some(rx) => tokio::select! {
+                    event = rx.recv() => match event {
+                        Ok(event) => self.matching(&event),
+                        // Missed some events: catch up by polling once.
+                        Err(RecvError::Lagged(_)) => self.check(&client).await,
+                        Err(RecvError::Closed) => {
+                            events = None;
+                            ticker = every(self.interval);
+                            Vec::new()
+                        }
+                    },
+                    _ = ticker.tick() => self.check(&client).await,
+                },
+                None => {
+                    ticker.tick().await;
+                    self.check(&client).await
+                }
+            };
+            {
+                // Decide to stop under the poller lock so a concurrent
+                // subscribe either sees this task finished or is seen here.
+                let mut poller = self.poller.lock().unwrap();
+                if self.watches.lock().unwrap().is_empty() {
+                    *poller = None;
+                    return;
+                }
+            }
+            let Some(peer) = self.peer.lock().unwrap().clone() else {
+                return;
+            };
+            if peer.is_transport_closed() {
+                return;
+            }
+            for uri in updated {
+                if let Err(e) = peer
+                    .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+                    .await
+                {
+                    tracing::debug!("Could not send resource update: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::MockSlack;
+
+    #[test]
+    fn timestamps_compare_numerically() {
+        let messages = vec![
+            json!({ "ts": "1700000000.000009" }),
+            json!({ "ts": "1700000000.000010" }),
+            json!({ "ts": "999999999.999999" }),
+        ];
+        assert_eq!(
+            newest_after(&messages, None).as_deref(),
+            Some("1700000000.000010")
+        );
+        assert_eq!(newest_after(&messages, Some("1700000000.000010")), None);
+    }
+
+    #[tokio::test]
+    async fn check_reports_new_channel_and_thread_messages() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general");
+        let parent = mock.add_message("C001", "U001", "question");
+        let client = mock.client();
+
+        let subs = Subscriptions::new(DEFAULT_POLL_INTERVAL, None);
+        let channel_uri = "slack://channel/C001".to_string();
+        let thread_uri = format!("slack://thread/C001/{}", parent);
+        for uri in [&channel_uri, &thread_uri] {
+            let target = SlackUri::parse(uri).unwrap();
+            let oldest = newest(&client, &target, None).await.unwrap().unwrap();
+            subs.watches
+                .lock()
+                .unwrap()
+                .insert(uri.clone(), Watch { target, oldest });
+        }
+        assert!(subs.check(&client).await.is_empty());
+
+        client
+            .post(
+                "chat.postMessage",
+                json!({ "channel": "C001", "text": "answer", "thread_ts": parent }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(subs.check(&client).await, vec![thread_uri.clone()]);
+        assert!(subs.check(&client).await.is_empty());
+
+        mock.add_message("C001", "U001", "new topic");
+        assert_eq!(subs.check(&client).await, vec![channel_uri]);
+
+        subs.unsubscribe(&thread_uri);
+        assert_eq!(subs.watches.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn message_events_match_channel_and_thread_uris() {
+        let subs = Subscriptions::new(DEFAULT_POLL_INTERVAL, Some(EventBus::new()));
+        for uri in ["slack://channel/C001", "slack://thread/C001/1.000001"] {
+            subs.watches.lock().unwrap().insert(
+                uri.to_string(),
+                Watch {
+                    target: SlackUri::parse(uri).unwrap(),
+                    oldest: "1.000001".to_string(),
+                },
+            );
+        }
+        let event = |v: Value| SlackEvent::from_event(&v).unwrap();
+
+        let reply = event(json!({
+            "type": "message", "channel": "C001", "ts": "2.000001", "thread_ts": "1.000001",
+        }));
+        assert_eq!(subs.matching(&reply), vec!["slack://thread/C001/1.000001"]);
+
+        let top_level = event(json!({ "type": "message", "channel": "C001", "ts": "3.000001" }));
+        assert_eq!(subs.matching(&top_level), vec!["slack://channel/C001"]);
+
+        let elsewhere = event(json!({ "type": "message", "channel": "C002", "ts": "4.000001" }));
+        assert!(subs.matching(&elsewhere).is_empty());
+        let reaction = event(json!({
+            "type": "reaction_added", "item": { "channel": "C001", "ts": "1.000001" },
+        }));
+        assert!(subs.matching(&reaction).is_empty());
+    }
+}
@@ -1,33 +1,140 @@
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, tool::Parameters},
-    model::*, tool, tool_handler, tool_router, ServerHandler,
+    handler::server::{
+        router::tool::ToolRouter,
+        tool::{Parameters, ToolCallContext},
+    },
+    model::*,
+    service::RequestContext,
+    tool, tool_router, Peer, RoleServer, ServerHandler,
 };
-use serde_json::json;
+use serde_json::{json, Value};
 use std::borrow::Cow;
 use std::future::Future;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
-use crate::slack_client::SlackClient;
+use crate::approval;
+use crate::auth;
+use crate::blocks;
+use crate::directory::{self, Directory};
+use crate::error::SlackError;
+use crate::events::EventBus;
+use crate::files;
+use crate::markdown::{self, MessageFormat};
+use crate::posted::PostedMessages;
+use crate::resources;
+use crate::schedule;
+use crate::shape::{self, OutputMode};
+use crate::slack_client::{PageLimits, SlackClient, TokenKind};
+use crate::subscriptions::{self, Subscriptions};
 use crate::types::*;
+use crate::wait::{self, ReplyFilter};
 
 #[derive(Clone)]
 pub struct SlackTools {
     client: Arc<SlackClient>,
+    directory: Arc<Directory>,
     default_channel: Option<String>,
+    output_mode: OutputMode,
+    posted: Arc<PostedMessages>,
+    allow_unowned_edits: bool,
+    upload_dir: Option<PathBuf>,
+    poll_interval: Duration,
+    events: Option<EventBus>,
+    subscriptions: Arc<Subscriptions>,
     tool_router: ToolRouter<SlackTools>,
 }
 
 impl SlackTools {
-    pub fn new(client: SlackClient, default_channel: Option<String>) -> Self {
+    pub fn new(client: impl Into<Arc<SlackClient>>, default_channel: Option<String>) -> Self {
         Self {
-            client: Arc::new(client),
+            client: client.into(),
+            directory: Arc::new(Directory::new()),
             default_channel,
+            output_mode: OutputMode::default(),
+            posted: Arc::new(PostedMessages::new()),
+            allow_unowned_edits: false,
+            upload_dir: None,
+            poll_interval: subscriptions::DEFAULT_POLL_INTERVAL,
+            events: None,
+            subscriptions: Arc::new(Subscriptions::new(subscriptions::DEFAULT_POLL_INTERVAL, None)),
             tool_router: Self::tool_router(),
         }
     }
 
-    fn resolve_channel(&self, channel: Option<String>) -> Result<String, ErrorData> {
-        channel
+    /// Set the response format used when a call does not pick one itself.
+    pub fn with_output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    /// Let update_message and delete_message touch messages this server did
+    /// not post itself.
+    pub fn with_unowned_edits(mut self, allow: bool) -> Self {
+        self.allow_unowned_edits = allow;
+        self
+    }
+
+    /// Directory upload_file may read local files from; `None` disables
+    /// local paths.
+    pub fn with_upload_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.upload_dir = dir;
+        self
+    }
+
+    /// How often subscribed channels and threads are checked for new messages.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self.subscriptions = Arc::new(Subscriptions::new(interval, self.events.clone()));
+        self
+    }
+
+    /// Follow incoming Slack events (from Socket Mode or the Events API)
+    /// instead of polling.
+    pub fn with_events(mut self, events: Option<EventBus>) -> Self {
+        self.events = events;
+        self.subscriptions = Arc::new(Subscriptions::new(self.poll_interval, self.events.clone()));
+        self
+    }
+
+    /// A handle for a new MCP session: shares the client and caches, but
+    /// keeps its own resource subscriptions.
+    pub fn for_session(&self) -> Self {
+        let mut session = self.clone();
+        session.subscriptions =
+            Arc::new(Subscriptions::new(self.poll_interval, self.events.clone()));
+        session
+    }
+
+    /// A handle for a session acting as another Slack identity, once Slack
+    /// accepts `token`: nothing is shared with this one but its settings.
+    /// The event bus, default channel and upload directory belong to the
+    /// server's own identity, so they stay behind.
+    pub async fn for_tenant(&self, token: String) -> Result<Self, SlackError> {
+        let client = self.client.for_token(token);
+        client.call("auth.test", json!({})).await?;
+        Ok(Self::new(client, None)
+            .with_output_mode(self.output_mode)
+            .with_unowned_edits(self.allow_unowned_edits)
+            .with_poll_interval(self.poll_interval))
+    }
+
+    /// Whether any handle besides this one still uses its client.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.client) > 1
+    }
+
+    /// Resolve a channel reference (ID, `#name`, bare name or `@user` for a DM)
+    /// to a conversation ID, falling back to SLACK_DEFAULT_CHANNEL. DMs are
+    /// opened as `token`, the identity that will post in them.
+    async fn resolve_channel(
+        &self,
+        channel: Option<String>,
+        token: Option<TokenKind>,
+    ) -> Result<String, ErrorData> {
+        let channel = channel
             .filter(|c| !c.is_empty())
             .or_else(|| self.default_channel.clone())
             .ok_or_else(|| ErrorData {
@@ -36,32 +143,317 @@ impl SlackTools {
                     "channel is required (or set SLACK_DEFAULT_CHANNEL env var)",
                 ),
                 data: None,
-            })
+            })?;
+        self.directory
+            .resolve_channel(&self.client, token, &channel)
+            .await
+            .map_err(Self::err)
+    }
+
+    fn err(e: SlackError) -> ErrorData {
+        e.into()
     }
 
-    fn err(e: anyhow::Error) -> ErrorData {
+    /// Refuse callers whose API key or access token does not grant `scope`.
+    /// Requests without a principal came over stdio or an open transport.
+    fn authorize(context: &RequestContext<RoleServer>, scope: &str) -> Result<(), ErrorData> {
+        match auth::principal(&context.extensions) {
+            Some(principal) if !principal.scopes.allows(scope) => Err(ErrorData {
+                code: ErrorCode::INVALID_REQUEST,
+                message: Cow::from(format!("'{}' is not allowed to use {}", principal.name, scope)),
+                data: None,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> ErrorData {
         ErrorData {
-            code: ErrorCode(-32603),
-            message: Cow::from(e.to_string()),
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from(message.into()),
             data: None,
         }
     }
+
+    /// The token a tool call runs with: an explicit `as_user` wins; otherwise the
+    /// tool's preferred token if the server has it, else default routing
+    /// (which falls back to whatever token is configured).
+    fn token(&self, preferred: TokenKind, as_user: Option<bool>) -> Option<TokenKind> {
+        match as_user {
+            Some(true) => Some(TokenKind::User),
+            Some(false) => Some(TokenKind::Bot),
+            None => Some(preferred).filter(|k| self.client.has_token(*k)),
+        }
+    }
+
+    /// Remember a chat.postMessage result so it can be edited or deleted later.
+    fn record_posted(&self, data: &Value) {
+        if let (Some(channel), Some(ts)) = (data["channel"].as_str(), data["ts"].as_str()) {
+            self.posted.record(channel, ts);
+        }
+    }
+
+    /// Refuse to modify a message this server did not post, unless
+    /// SLACK_ALLOW_UNOWNED_EDITS is set.
+    fn check_owned(&self, channel: &str, ts: &str) -> Result<(), ErrorData> {
+        if self.allow_unowned_edits || self.posted.contains(channel, ts) {
+            return Ok(());
+        }
+        Err(ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from(format!(
+                "Message {} in {} was not posted by this server; refusing to modify it",
+                ts, channel
+            )),
+            data: Some(json!({
+                "channel": channel,
+                "ts": ts,
+                "hint": "Only messages posted through this server since it started can be edited \
+                         or deleted. Set SLACK_ALLOW_UNOWNED_EDITS=true on the server to lift this.",
+            })),
+        })
+    }
+
+    /// Validate `blocks` and `attachments` and build the `text`/`blocks`/
+    /// `attachments` part of a chat.postMessage or chat.update body. When no
+    /// text is given, a plain rendering of the blocks becomes the
+    /// notification fallback.
+    fn message_content(
+        text: Option<String>,
+        blocks: Option<Vec<Value>>,
+        attachments: Option<Vec<Value>>,
+    ) -> Result<Value, ErrorData> {
+        if let Some(ref blocks) = blocks {
+            blocks::validate_blocks(blocks, "blocks")?;
+        }
+        if let Some(ref attachments) = attachments {
+            blocks::validate_attachments(attachments, "attachments")?;
+        }
+
+        let text = text.filter(|t| !t.is_empty()).unwrap_or_else(|| {
+            let from_blocks = blocks.as_deref().map(blocks::fallback_text).unwrap_or_default();
+            match attachments.as_deref() {
+                Some(attachments) if from_blocks.is_empty() => {
+                    blocks::attachments_fallback(attachments)
+                }
+                _ => from_blocks,
+            }
+        });
+        if text.is_empty() && blocks.is_none() && attachments.is_none() {
+            return Err(ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from("Provide text, blocks or attachments"),
+                data: None,
+            });
+        }
+
+        let mut content = json!({ "text": text });
+        if let Some(blocks) = blocks {
+            content["blocks"] = json!(blocks);
+        }
+        if let Some(attachments) = attachments {
+            content["attachments"] = json!(attachments);
+        }
+        Ok(content)
+    }
+
+    /// Build the message bodies for `text` in `format`, plus any explicit
+    /// blocks and attachments. Content too long for one message comes back as
+    /// several parts; callers post the rest as thread replies to the first.
+    fn message_parts(
+        format: Option<MessageFormat>,
+        text: Option<String>,
+        blocks: Option<Value>,
+        attachments: Option<Value>,
+    ) -> Result<Vec<Value>, ErrorData> {
+        let blocks = blocks.map(|b| blocks::parse_array(b, "blocks")).transpose()?;
+        if let Some(ref blocks) = blocks {
+            blocks::validate_blocks(blocks, "blocks")?;
+        }
+        let mut attachments = attachments
+            .map(|a| blocks::parse_array(a, "attachments"))
+            .transpose()?;
+        let format = format.unwrap_or_default();
+
+        if format == MessageFormat::Markdown {
+            let mut all = markdown::to_blocks(text.as_deref().unwrap_or_default());
+            all.extend(blocks.unwrap_or_default());
+            if all.is_empty() {
+                return Ok(vec![Self::message_content(None, None, attachments)?]);
+            }
+            return all
+                .chunks(blocks::MAX_BLOCKS)
+                .map(|chunk| Self::message_content(None, Some(chunk.to_vec()), attachments.take()))
+                .collect();
+        }
+
+        let text = text.unwrap_or_default();
+        let text = match format {
+            MessageFormat::Plain => markdown::escape(&text),
+            _ => text,
+        };
+        let mut chunks = markdown::split_text(&text, blocks::MAX_MESSAGE_TEXT).into_iter();
+        let mut parts = vec![Self::message_content(chunks.next(), blocks, attachments)?];
+        for chunk in chunks {
+            parts.push(Self::message_content(Some(chunk), None, None)?);
+        }
+        if format == MessageFormat::Plain {
+            for part in &mut parts {
+                part["mrkdwn"] = json!(false);
+            }
+        }
+        Ok(parts)
+    }
+
+    /// Post message parts to `channel`. The first goes to `thread_ts` (or the
+    /// channel); the rest follow as replies in the same thread. Returns the
+    /// first response, listing the follow-up timestamps under
+    /// `continued_in_thread`.
+    async fn post_parts(
+        &self,
+        token: Option<TokenKind>,
+        channel: String,
+        thread_ts: Option<String>,
+        reply_broadcast: bool,
+        parts: Vec<Value>,
+    ) -> Result<Value, ErrorData> {
+        let mut first = None;
+        let mut thread_ts = thread_ts;
+        let mut continued = Vec::new();
+        for mut body in parts {
+            body["channel"] = json!(channel);
+            if let Some(ref ts) = thread_ts {
+                body["thread_ts"] = json!(ts);
+            }
+            if reply_broadcast && first.is_none() {
+                body["reply_broadcast"] = json!(true);
+            }
+            let data = self
+                .client
+                .post_with(token, "chat.postMessage", body)
+                .await
+                .map_err(Self::err)?;
+            self.record_posted(&data);
+            if first.is_none() {
+                thread_ts = thread_ts.or_else(|| data["ts"].as_str().map(String::from));
+                first = Some(data);
+            } else {
+                continued.push(data["ts"].clone());
+            }
+        }
+        let mut data = first.unwrap_or_default();
+        if !continued.is_empty() {
+            data["continued_in_thread"] = json!(continued);
+        }
+        Ok(data)
+    }
+
+    fn respond(&self, data: Value, output: Option<OutputMode>) -> CallToolResult {
+        let text = shape::render(data, output.unwrap_or(self.output_mode));
+        CallToolResult::success(vec![Content::text(text)])
+    }
+
+    /// Run `work` until it finishes, or until `timeout` passes (`Ok(None)`) or
+    /// the request is cancelled. Meanwhile send progress notifications if the
+    /// client asked for them.
+    async fn wait_with_progress<T>(
+        work: impl Future<Output = Result<T, ErrorData>>,
+        timeout: Duration,
+        what: &str,
+        meta: &Meta,
+        ct: &CancellationToken,
+        peer: &Peer<RoleServer>,
+    ) -> Result<Option<T>, ErrorData> {
+        let started = tokio::time::Instant::now();
+        let progress_token = meta.get_progress_token();
+        tokio::pin!(work);
+        let mut ticker = tokio::time::interval(wait::PROGRESS_INTERVAL);
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                done = &mut work => return done.map(Some),
+                _ = tokio::time::sleep_until(started + timeout) => return Ok(None),
+                _ = ct.cancelled() => {
+                    return Err(ErrorData {
+                        code: ErrorCode::INTERNAL_ERROR,
+                        message: Cow::from(format!("Cancelled while waiting for {}", what)),
+                        data: None,
+                    });
+                }
+                _ = ticker.tick() => {
+                    if let Some(token) = &progress_token {
+                        let waited = started.elapsed().as_secs() as u32;
+                        let _ = peer
+                            .notify_progress(ProgressNotificationParam {
+                                progress_token: token.clone(),
+                                progress: waited,
+                                total: Some(timeout.as_secs() as u32),
+                                message: Some(format!("Waiting for {} ({}s)", what, waited)),
+                            })
+                            .await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetch a cursor-paginated method. Returns Slack's single raw page unless
+    /// the caller asked for `max_items` or `all`, in which case pages are
+    /// followed and merged under `items_key`.
+    async fn fetch_list(
+        &self,
+        method: &str,
+        body: Value,
+        items_key: &str,
+        max_items: Option<u32>,
+        all: Option<bool>,
+    ) -> Result<Value, ErrorData> {
+        if max_items.is_none() && all != Some(true) {
+            return self.client.call(method, body).await.map_err(Self::err);
+        }
+        let limits = PageLimits {
+            max_items: max_items.map(|n| n as usize),
+            max_tokens: Some(PAGINATION_TOKEN_BUDGET),
+        };
+        let page = self
+            .client
+            .paginate(method, body, items_key, limits)
+            .await
+            .map_err(Self::err)?;
+        Ok(page.to_json(items_key))
+    }
+}
+
+/// Prefix `value` with `sigil` unless it already has it. Raw Slack IDs are
+/// wrapped as `<#C0123…>` / `<@U0123…>`, the forms search resolves.
+fn with_sigil(value: String, sigil: char) -> String {
+    let prefixes: &[char] = if sigil == '@' { &['U', 'W'] } else { &['C', 'G', 'D'] };
+    if value.starts_with(sigil) || value.starts_with('<') {
+        value
+    } else if directory::is_slack_id(&value, prefixes) {
+        format!("<{}{}>", sigil, value)
+    } else {
+        format!("{}{}", sigil, value)
+    }
 }
 
+/// Upper bound on the size of a merged multi-page result, so "fetch all" on a
+/// busy channel cannot flood the model's context.
+const PAGINATION_TOKEN_BUDGET: usize = 25_000;
+
 #[tool_router]
 impl SlackTools {
-    #[tool(description = "Post a message to a Slack channel")]
+    #[tool(description = "Post a message to a Slack channel, as text and/or Block Kit blocks")]
     async fn post_message(
         &self,
         Parameters(params): Parameters<PostMessageParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let channel = self.resolve_channel(params.channel)?;
-        let body = json!({
-            "channel": channel,
-            "text": params.text,
-        });
-        let data = self.client.post("chat.postMessage", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let parts =
+            Self::message_parts(params.format, params.text, params.blocks, params.attachments)?;
+        let channel = self.resolve_channel(params.channel, token).await?;
+        let data = self.post_parts(token, channel, None, false, parts).await?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Reply to a message in a thread")]
@@ -69,31 +461,391 @@ impl SlackTools {
         &self,
         Parameters(params): Parameters<ReplyToMessageParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let mut body = json!({
-            "channel": params.channel,
-            "text": params.text,
-            "thread_ts": params.thread_ts,
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let parts =
+            Self::message_parts(params.format, params.text, params.blocks, params.attachments)?;
+        let channel = self.resolve_channel(Some(params.channel), token).await?;
+        let broadcast = params.reply_broadcast == Some(true);
+        let data = self
+            .post_parts(token, channel, Some(params.thread_ts), broadcast, parts)
+            .await?;
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "Edit the text or blocks of a message this server posted earlier")]
+    async fn update_message(
+        &self,
+        Parameters(params): Parameters<UpdateMessageParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let channel = self.resolve_channel(Some(params.channel), token).await?;
+        self.check_owned(&channel, &params.ts)?;
+        let mut parts =
+            Self::message_parts(params.format, params.text, params.blocks, params.attachments)?;
+        if parts.len() > 1 {
+            return Err(ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(
+                    "The new content does not fit in one message; shorten it, or post the rest as a reply",
+                ),
+                data: None,
+            });
+        }
+        let mut body = parts.remove(0);
+        body["channel"] = json!(channel);
+        body["ts"] = json!(params.ts);
+        let data = self
+            .client
+            .post_with(token, "chat.update", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "Delete a message this server posted earlier")]
+    async fn delete_message(
+        &self,
+        Parameters(params): Parameters<DeleteMessageParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let channel = self.resolve_channel(Some(params.channel), token).await?;
+        self.check_owned(&channel, &params.ts)?;
+        let body = json!({
+            "channel": channel,
+            "ts": params.ts,
+        });
+        let data = self
+            .client
+            .post_with(token, "chat.delete", body)
+            .await
+            .map_err(Self::err)?;
+        self.posted.forget(&channel, &params.ts);
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "Post a message in a channel that only one user can see")]
+    async fn post_ephemeral(
+        &self,
+        Parameters(params): Parameters<PostEphemeralParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let mut parts =
+            Self::message_parts(params.format, params.text, params.blocks, params.attachments)?;
+        if parts.len() > 1 {
+            return Err(ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from("The message does not fit in one ephemeral message; shorten it"),
+                data: None,
+            });
+        }
+        let user = params.user.trim();
+        let user = self
+            .directory
+            .resolve_user(&self.client, user.strip_prefix('@').unwrap_or(user))
+            .await
+            .map_err(Self::err)?;
+        let mut body = parts.remove(0);
+        body["channel"] = json!(self.resolve_channel(params.channel, token).await?);
+        body["user"] = json!(user);
+        if let Some(thread_ts) = params.thread_ts.filter(|t| !t.is_empty()) {
+            body["thread_ts"] = json!(thread_ts);
+        }
+        let data = self
+            .client
+            .post_with(token, "chat.postEphemeral", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "Send a direct message to one or more users by ID or @name, opening the DM or group DM if needed")]
+    async fn send_direct_message(
+        &self,
+        Parameters(params): Parameters<SendDirectMessageParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        if params.users.is_empty() {
+            return Err(ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from("users must list at least one user"),
+                data: None,
+            });
+        }
+        let parts =
+            Self::message_parts(params.format, params.text, params.blocks, params.attachments)?;
+        let channel = self
+            .directory
+            .open_dm(&self.client, token, &params.users)
+            .await
+            .map_err(Self::err)?;
+        let data = self.post_parts(token, channel, None, false, parts).await?;
+        Ok(self.respond(data, None))
+    }
+
+    // ─── Scheduled messages ─────────────────────────────────
+
+    #[tool(description = "Schedule a message (or thread reply) to be posted later. post_at takes a Unix timestamp or an ISO-8601 time with a time zone.")]
+    async fn schedule_message(
+        &self,
+        Parameters(params): Parameters<ScheduleMessageParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let post_at = params.post_at.resolve(chrono::Utc::now()).map_err(|e| ErrorData {
+            code: ErrorCode::INVALID_PARAMS,
+            message: Cow::from(e),
+            data: None,
+        })?;
+        let mut parts =
+            Self::message_parts(params.format, params.text, params.blocks, params.attachments)?;
+        if parts.len() > 1 {
+            return Err(ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from(
+                    "The message does not fit in one scheduled message; shorten it or schedule several",
+                ),
+                data: None,
+            });
+        }
+        let mut body = parts.remove(0);
+        body["channel"] = json!(self.resolve_channel(params.channel, token).await?);
+        body["post_at"] = json!(post_at);
+        if let Some(thread_ts) = params.thread_ts.filter(|t| !t.is_empty()) {
+            body["thread_ts"] = json!(thread_ts);
+            if let Some(true) = params.reply_broadcast {
+                body["reply_broadcast"] = json!(true);
+            }
+        }
+        let mut data = self
+            .client
+            .post_with(token, "chat.scheduleMessage", body)
+            .await
+            .map_err(Self::err)?;
+        data["post_at_utc"] = json!(schedule::to_rfc3339(post_at));
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "List messages scheduled by this app that have not been posted yet")]
+    async fn list_scheduled_messages(
+        &self,
+        Parameters(params): Parameters<ListScheduledMessagesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let mut body = json!({});
+        if let Some(channel) = params.channel.filter(|c| !c.is_empty()) {
+            body["channel"] = json!(self.resolve_channel(Some(channel), token).await?);
+        }
+        if let Some(limit) = params.limit {
+            body["limit"] = json!(limit);
+        }
+        if let Some(ref cursor) = params.cursor {
+            body["cursor"] = json!(cursor);
+        }
+        let mut data = self
+            .client
+            .post_with(token, "chat.scheduledMessages.list", body)
+            .await
+            .map_err(Self::err)?;
+        if let Some(messages) = data["scheduled_messages"].as_array_mut() {
+            for m in messages {
+                if let Some(post_at) = m["post_at"].as_i64() {
+                    m["post_at_utc"] = json!(schedule::to_rfc3339(post_at));
+                }
+            }
+        }
+        Ok(self.respond(data, params.output))
+    }
+
+    #[tool(description = "Cancel a scheduled message before it is posted")]
+    async fn delete_scheduled_message(
+        &self,
+        Parameters(params): Parameters<DeleteScheduledMessageParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let channel = self.resolve_channel(Some(params.channel), token).await?;
+        let body = json!({
+            "channel": channel,
+            "scheduled_message_id": params.scheduled_message_id,
         });
-        if let Some(true) = params.reply_broadcast {
-            body["reply_broadcast"] = json!(true);
+        let data = self
+            .client
+            .post_with(token, "chat.deleteScheduledMessage", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
+    }
+
+    // ─── Files ──────────────────────────────────────────────
+
+    #[tool(description = "Upload a file (text, base64 or a local file from the server's upload directory) and optionally share it in a channel or thread")]
+    async fn upload_file(
+        &self,
+        Parameters(params): Parameters<UploadFileParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let (local_name, bytes) = match (params.content, params.content_base64, params.path) {
+            (Some(text), None, None) => (None, text.into_bytes()),
+            (None, Some(data), None) => {
+                (None, files::decode_base64(&data).map_err(Self::invalid_params)?)
+            }
+            (None, None, Some(path)) => {
+                let (name, bytes) = files::read_local(self.upload_dir.as_deref(), &path)
+                    .map_err(Self::invalid_params)?;
+                (Some(name), bytes)
+            }
+            _ => {
+                return Err(Self::invalid_params(
+                    "Provide exactly one of content, content_base64 or path",
+                ))
+            }
+        };
+        if bytes.len() > files::MAX_UPLOAD_BYTES {
+            return Err(Self::invalid_params(format!(
+                "File is {} bytes; the upload limit is {}",
+                bytes.len(),
+                files::MAX_UPLOAD_BYTES
+            )));
+        }
+        let filename = params
+            .filename
+            .filter(|f| !f.is_empty())
+            .or(local_name)
+            .ok_or_else(|| Self::invalid_params("filename is required with content or content_base64"))?;
+        let channel = match params.channel.filter(|c| !c.is_empty()) {
+            Some(channel) => Some(self.resolve_channel(Some(channel), token).await?),
+            None if params.thread_ts.is_some() => {
+                return Err(Self::invalid_params("thread_ts requires channel"))
+            }
+            None => None,
+        };
+
+        let mut body = json!({ "filename": filename, "length": bytes.len() });
+        if let Some(alt_text) = params.alt_text {
+            body["alt_txt"] = json!(alt_text);
+        }
+        if let Some(snippet_type) = params.snippet_type {
+            body["snippet_type"] = json!(snippet_type);
         }
-        let data = self.client.post("chat.postMessage", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let target = self
+            .client
+            .call_with(token, "files.getUploadURLExternal", body)
+            .await
+            .map_err(Self::err)?;
+        let (Some(upload_url), Some(file_id)) =
+            (target["upload_url"].as_str(), target["file_id"].as_str())
+        else {
+            return Err(Self::err(SlackError::Api {
+                method: "files.getUploadURLExternal".to_string(),
+                error: "missing upload_url or file_id in response".to_string(),
+            }));
+        };
+        self.client.upload(upload_url, bytes).await.map_err(Self::err)?;
+
+        let title = params.title.unwrap_or_else(|| filename.clone());
+        let mut body = json!({ "files": [{ "id": file_id, "title": title }] });
+        if let Some(channel) = channel {
+            body["channel_id"] = json!(channel);
+        }
+        if let Some(thread_ts) = params.thread_ts {
+            body["thread_ts"] = json!(thread_ts);
+        }
+        if let Some(comment) = params.initial_comment {
+            body["initial_comment"] = json!(comment);
+        }
+        let data = self
+            .client
+            .call_with(token, "files.completeUploadExternal", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "Get a file's metadata: name, type, size, sharing and links")]
+    async fn get_file_info(
+        &self,
+        Parameters(params): Parameters<GetFileInfoParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let body = json!({ "file": params.file_id });
+        let data = self.client.call("files.info", body).await.map_err(Self::err)?;
+        Ok(self.respond(data, params.output))
     }
 
+    #[tool(description = "Download a file's contents. Text files come back as text; other files as an embedded base64 resource.")]
+    async fn download_file(
+        &self,
+        Parameters(params): Parameters<DownloadFileParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let info = self
+            .client
+            .call_with(token, "files.info", json!({ "file": params.file_id }))
+            .await
+            .map_err(Self::err)?;
+        let file = &info["file"];
+        let size = file["size"].as_u64().unwrap_or(0);
+        if size > files::MAX_DOWNLOAD_BYTES {
+            return Err(Self::invalid_params(format!(
+                "File is {} bytes; download_file returns at most {}. Use its permalink instead.",
+                size,
+                files::MAX_DOWNLOAD_BYTES
+            )));
+        }
+        let url = file["url_private_download"]
+            .as_str()
+            .or_else(|| file["url_private"].as_str())
+            .ok_or_else(|| Self::invalid_params("This file has no downloadable contents"))?;
+        let (bytes, content_type) = self.client.download(token, url).await.map_err(Self::err)?;
+
+        let mime_type = file["mimetype"]
+            .as_str()
+            .map(String::from)
+            .or(content_type)
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        let uri = format!("slack://file/{}", params.file_id);
+        let contents = match String::from_utf8(bytes) {
+            Ok(text) if files::is_text_mime(&mime_type) => ResourceContents::TextResourceContents {
+                uri,
+                mime_type: Some(mime_type),
+                text,
+            },
+            Ok(text) => ResourceContents::BlobResourceContents {
+                uri,
+                mime_type: Some(mime_type),
+                blob: files::encode_base64(text.as_bytes()),
+            },
+            Err(e) => ResourceContents::BlobResourceContents {
+                uri,
+                mime_type: Some(mime_type),
+                blob: files::encode_base64(e.as_bytes()),
+            },
+        };
+        let meta = shape::render(json!({ "file": file }), OutputMode::Compact);
+        Ok(CallToolResult::success(vec![
+            Content::text(meta),
+            Content::resource(contents),
+        ]))
+    }
+
+    // ─── Canvas ─────────────────────────────────────────────
+
     #[tool(description = "Create a Slack canvas with markdown content. If channel_id is provided, creates a channel-bound canvas; otherwise a standalone canvas.")]
     async fn create_canvas(
         &self,
         Parameters(params): Parameters<CreateCanvasParams>,
     ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
         let document_content = json!({
             "type": "markdown",
             "markdown": params.markdown,
         });
 
-        let data = if let Some(channel_id) = params.channel_id.filter(|c| !c.is_empty()) {
+        let channel_id = match params.channel_id.filter(|c| !c.is_empty()) {
+            Some(channel) => Some(self.resolve_channel(Some(channel), token).await?),
+            None => None,
+        };
+        let data = if let Some(channel_id) = channel_id {
             self.client
-                .post(
+                .post_with(
+                    token,
                     "conversations.canvases.create",
                     json!({
                         "channel_id": channel_id,
@@ -103,7 +855,8 @@ impl SlackTools {
                 .await
         } else {
             self.client
-                .post(
+                .post_with(
+                    token,
                     "canvases.create",
                     json!({
                         "title": params.title,
@@ -114,7 +867,7 @@ impl SlackTools {
         }
         .map_err(Self::err)?;
 
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Update an existing Slack canvas by replacing all content with new markdown")]
@@ -132,8 +885,13 @@ impl SlackTools {
                 },
             }],
         });
-        let data = self.client.post("canvases.edit", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "canvases.edit", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "List Slack channels the bot has access to")]
@@ -151,8 +909,21 @@ impl SlackTools {
         if let Some(ref types) = params.types {
             body["types"] = json!(types);
         }
-        let data = self.client.post("conversations.list", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let data = self
+            .fetch_list("conversations.list", body, "channels", params.max_items, params.all)
+            .await?;
+        Ok(self.respond(data, params.output))
+    }
+
+    #[tool(description = "Join a public Slack channel so the bot can read and post in it")]
+    async fn join_channel(
+        &self,
+        Parameters(params): Parameters<JoinChannelParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let channel = self.resolve_channel(Some(params.channel), None).await?;
+        let body = json!({ "channel": channel });
+        let data = self.client.post("conversations.join", body).await.map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Get recent messages from a Slack channel")]
@@ -160,19 +931,21 @@ impl SlackTools {
         &self,
         Parameters(params): Parameters<ListMessagesParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let mut body = json!({ "channel": params.channel });
+        let channel = self.resolve_channel(Some(params.channel), None).await?;
+        let mut body = json!({ "channel": channel });
         if let Some(limit) = params.limit {
             body["limit"] = json!(limit);
         }
         if let Some(ref cursor) = params.cursor {
             body["cursor"] = json!(cursor);
         }
-        let data = self
-            .client
-            .post("conversations.history", body)
-            .await
-            .map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let mut data = self
+            .fetch_list("conversations.history", body, "messages", params.max_items, params.all)
+            .await?;
+        if let Some(true) = params.resolve_names {
+            self.directory.resolve_messages(&self.client, &mut data["messages"]).await;
+        }
+        Ok(self.respond(data, params.output))
     }
 
     #[tool(description = "Get all replies in a message thread")]
@@ -180,8 +953,9 @@ impl SlackTools {
         &self,
         Parameters(params): Parameters<GetThreadRepliesParams>,
     ) -> Result<CallToolResult, ErrorData> {
+        let channel = self.resolve_channel(Some(params.channel), None).await?;
         let mut body = json!({
-            "channel": params.channel,
+            "channel": channel,
             "ts": params.ts,
         });
         if let Some(limit) = params.limit {
@@ -190,12 +964,212 @@ impl SlackTools {
         if let Some(ref cursor) = params.cursor {
             body["cursor"] = json!(cursor);
         }
+        let mut data = self
+            .fetch_list("conversations.replies", body, "messages", params.max_items, params.all)
+            .await?;
+        if let Some(true) = params.resolve_names {
+            self.directory.resolve_messages(&self.client, &mut data["messages"]).await;
+        }
+        Ok(self.respond(data, params.output))
+    }
+
+    #[tool(description = "Wait for someone to reply in a thread, optionally only from one user and only with text matching a regex. Returns the reply, or status 'timed_out'. Sends progress notifications while waiting and stops when the request is cancelled.")]
+    async fn wait_for_reply(
+        &self,
+        Parameters(params): Parameters<WaitForReplyParams>,
+        meta: Meta,
+        ct: CancellationToken,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let channel = self.resolve_channel(Some(params.channel), None).await?;
+        let pattern = params
+            .pattern
+            .filter(|p| !p.is_empty())
+            .map(|p| regex::Regex::new(&p))
+            .transpose()
+            .map_err(|e| Self::invalid_params(format!("pattern is not a valid regex: {}", e)))?;
+        let user = match params.user.filter(|u| !u.is_empty()) {
+            Some(user) => Some(
+                self.directory
+                    .resolve_user(&self.client, user.strip_prefix('@').unwrap_or(&user))
+                    .await
+                    .map_err(Self::err)?,
+            ),
+            None => None,
+        };
+        let after = match params.after_ts {
+            Some(ts) => ts,
+            None => wait::latest_in_thread(&self.client, &channel, &params.thread_ts)
+                .await
+                .map_err(Self::err)?,
+        };
+        let timeout = params
+            .timeout_seconds
+            .map_or(wait::DEFAULT_TIMEOUT, Duration::from_secs)
+            .min(wait::MAX_TIMEOUT);
+        let filter = ReplyFilter {
+            channel,
+            thread_ts: params.thread_ts,
+            after,
+            user,
+            pattern,
+        };
+
+        let started = tokio::time::Instant::now();
+        let reply = async {
+            wait::next_reply(&self.client, self.events.as_ref(), &filter)
+                .await
+                .map_err(Self::err)
+        };
+        let reply = Self::wait_with_progress(reply, timeout, "a reply", &meta, &ct, &peer).await?;
+
+        let data = match reply {
+            Some(message) => {
+                let mut messages = json!([message]);
+                self.directory.resolve_messages(&self.client, &mut messages).await;
+                json!({ "status": "replied", "message": messages[0] })
+            }
+            None => json!({
+                "status": "timed_out",
+                "waited_seconds": started.elapsed().as_secs(),
+            }),
+        };
+        Ok(self.respond(data, params.output))
+    }
+
+    #[tool(description = "Ask a human to approve an action: posts a message with Approve and Reject buttons and a comment box, waits for an answer, then updates the message to show the outcome. Returns decision 'approved', 'rejected' or 'expired', the approver and their comment. Needs Socket Mode or the interactivity endpoint to receive the click.")]
+    async fn request_approval(
+        &self,
+        Parameters(params): Parameters<RequestApprovalParams>,
+        meta: Meta,
+        ct: CancellationToken,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let bus = self.events.as_ref().ok_or_else(|| {
+            Self::invalid_params(
+                "request_approval cannot receive button clicks: set SLACK_APP_TOKEN for Socket Mode, \
+                 or SLACK_SIGNING_SECRET with TRANSPORT=sse or streamable-http for the interactivity \
+                 endpoint",
+            )
+        })?;
+        let channel = self.resolve_channel(params.channel, None).await?;
+        let mut approvers = Vec::new();
+        for user in params.approvers.unwrap_or_default() {
+            approvers.push(
+                self.directory
+                    .resolve_user(&self.client, user.strip_prefix('@').unwrap_or(&user))
+                    .await
+                    .map_err(Self::err)?,
+            );
+        }
+        let timeout = params
+            .timeout_seconds
+            .map_or(approval::DEFAULT_TIMEOUT, Duration::from_secs)
+            .min(wait::MAX_TIMEOUT);
+
+        let id = approval::new_id();
+        let fallback = format!("Approval requested: {}", params.question);
+        let mut body = json!({
+            "channel": channel,
+            "text": fallback,
+            "blocks": approval::request_blocks(&id, &params.question, params.details.as_deref()),
+        });
+        if let Some(thread_ts) = &params.thread_ts {
+            body["thread_ts"] = json!(thread_ts);
+        }
+        // Listen before posting so an instant click is not missed.
+        let events = bus.subscribe();
+        let posted = self
+            .client
+            .post_with(Some(TokenKind::Bot), "chat.postMessage", body)
+            .await
+            .map_err(Self::err)?;
+        self.record_posted(&posted);
+        let ts = posted["ts"].as_str().unwrap_or_default().to_string();
+        let posted_in = posted["channel"].as_str().unwrap_or(&channel).to_string();
+
+        let answer = async { Ok(approval::wait(events, &id, &posted_in, &ts, &approvers).await) };
+        let waited = Self::wait_with_progress(answer, timeout, "an approval", &meta, &ct, &peer).await;
+        let decision = match &waited {
+            Ok(decision) => decision.clone().flatten(),
+            Err(_) => None,
+        };
+        let outcome = match waited {
+            Ok(_) => approval::outcome_text(decision.as_ref()),
+            Err(_) => ":no_entry_sign: Withdrawn by the requester".to_string(),
+        };
+        let update = json!({
+            "channel": posted_in,
+            "ts": ts,
+            "text": format!("{} {}", fallback, outcome),
+            "blocks": approval::outcome_blocks(&params.question, &outcome),
+        });
+        if let Err(e) = self
+            .client
+            .post_with(Some(TokenKind::Bot), "chat.update", update)
+            .await
+        {
+            tracing::warn!("Could not update approval request {}: {}", ts, e);
+        }
+        waited?;
+
+        let data = match decision {
+            Some(d) => json!({
+                "decision": if d.approved { "approved" } else { "rejected" },
+                "approver": { "id": d.user_id, "name": d.user_name },
+                "comment": d.comment,
+                "channel": posted_in,
+                "ts": ts,
+            }),
+            None => json!({ "decision": "expired", "channel": posted_in, "ts": ts }),
+        };
+        Ok(self.respond(data, None))
+    }
+
+    #[tool(description = "Search messages across the workspace with Slack search syntax (in:, from:, before:, after:, has:). Requires SLACK_USER_TOKEN, since Slack rejects bot tokens for search.")]
+    async fn search_messages(
+        &self,
+        Parameters(params): Parameters<SearchMessagesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut query = params.query;
+        let modifiers = [
+            ("in", params.in_channel.map(|c| with_sigil(c, '#'))),
+            ("from", params.from_user.map(|u| with_sigil(u, '@'))),
+            ("before", params.before),
+            ("after", params.after),
+            ("has", params.has),
+        ];
+        for (modifier, value) in modifiers {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                query.push_str(&format!(" {}:{}", modifier, value));
+            }
+        }
+
+        let mut body = json!({ "query": query.trim() });
+        if let Some(sort) = params.sort {
+            body["sort"] = json!(sort);
+        }
+        if let Some(sort_dir) = params.sort_dir {
+            body["sort_dir"] = json!(sort_dir);
+        }
+        if let Some(count) = params.count {
+            body["count"] = json!(count);
+        }
+        if let Some(page) = params.page {
+            body["page"] = json!(page);
+        }
         let data = self
             .client
-            .post("conversations.replies", body)
+            .call_with(Some(TokenKind::User), "search.messages", body)
             .await
             .map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+
+        let output = params.output.unwrap_or(self.output_mode);
+        let data = match output {
+            OutputMode::Raw => data,
+            _ => shape::search_results(data),
+        };
+        Ok(self.respond(data, Some(output)))
     }
 
     #[tool(description = "Add an emoji reaction to a message")]
@@ -203,13 +1177,19 @@ impl SlackTools {
         &self,
         Parameters(params): Parameters<AddReactionParams>,
     ) -> Result<CallToolResult, ErrorData> {
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let channel = self.resolve_channel(Some(params.channel), token).await?;
         let body = json!({
-            "channel": params.channel,
+            "channel": channel,
             "timestamp": params.timestamp,
             "name": params.name,
         });
-        let data = self.client.post("reactions.add", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let data = self
+            .client
+            .post_with(token, "reactions.add", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "List users in the Slack workspace")]
@@ -224,8 +1204,10 @@ impl SlackTools {
         if let Some(ref cursor) = params.cursor {
             body["cursor"] = json!(cursor);
         }
-        let data = self.client.post("users.list", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let data = self
+            .fetch_list("users.list", body, "members", params.max_items, params.all)
+            .await?;
+        Ok(self.respond(data, params.output))
     }
 
     // ─── Lists ──────────────────────────────────────────────
@@ -245,8 +1227,13 @@ impl SlackTools {
         if let Some(schema) = params.schema {
             body["schema"] = schema;
         }
-        let data = self.client.post("lists.create", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.create", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Update a Slack list's name, description, or todo mode")]
@@ -264,8 +1251,13 @@ impl SlackTools {
         if let Some(todo_mode) = params.todo_mode {
             body["todo_mode"] = json!(todo_mode);
         }
-        let data = self.client.post("lists.update", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.update", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Add an item to a Slack list")]
@@ -277,8 +1269,13 @@ impl SlackTools {
         if let Some(initial_fields) = params.initial_fields {
             body["initial_fields"] = initial_fields;
         }
-        let data = self.client.post("lists.items.create", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.items.create", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "List all items in a Slack list")]
@@ -296,8 +1293,10 @@ impl SlackTools {
         if let Some(archived) = params.archived {
             body["archived"] = json!(archived);
         }
-        let data = self.client.post("lists.items.list", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let data = self
+            .fetch_list("lists.items.list", body, "items", params.max_items, params.all)
+            .await?;
+        Ok(self.respond(data, params.output))
     }
 
     #[tool(description = "Get a specific item from a Slack list")]
@@ -309,8 +1308,13 @@ impl SlackTools {
             "list_id": params.list_id,
             "id": params.id,
         });
-        let data = self.client.post("lists.items.info", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.items.info", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, params.output))
     }
 
     #[tool(description = "Update fields on a Slack list item")]
@@ -322,8 +1326,13 @@ impl SlackTools {
             "list_id": params.list_id,
             "cells": params.cells,
         });
-        let data = self.client.post("lists.items.update", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.items.update", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Delete a single item from a Slack list")]
@@ -335,8 +1344,13 @@ impl SlackTools {
             "list_id": params.list_id,
             "id": params.id,
         });
-        let data = self.client.post("lists.items.delete", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.items.delete", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Bulk delete multiple items from a Slack list")]
@@ -348,8 +1362,13 @@ impl SlackTools {
             "list_id": params.list_id,
             "ids": params.ids,
         });
-        let data = self.client.post("lists.items.deleteMultiple", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.items.deleteMultiple", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Grant read, write, or owner access to a Slack list for users or channels")]
@@ -367,8 +1386,13 @@ impl SlackTools {
         if let Some(user_ids) = params.user_ids {
             body["user_ids"] = user_ids;
         }
-        let data = self.client.post("lists.access.set", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.access.set", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 
     #[tool(description = "Revoke access to a Slack list from users or channels")]
@@ -383,23 +1407,917 @@ impl SlackTools {
         if let Some(user_ids) = params.user_ids {
             body["user_ids"] = user_ids;
         }
-        let data = self.client.post("lists.access.delete", body).await.map_err(Self::err)?;
-        Ok(CallToolResult::success(vec![Content::text(data.to_string())]))
+        let token = self.token(TokenKind::Bot, params.as_user);
+        let data = self
+            .client
+            .post_with(token, "lists.access.delete", body)
+            .await
+            .map_err(Self::err)?;
+        Ok(self.respond(data, None))
     }
 }
 
-#[tool_handler]
 impl ServerHandler for SlackTools {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            protocol_version: ProtocolVersion::LATEST,
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(
-                "Slack integration tools. Requires SLACK_TOKEN env var (also accepts SLACK_BOT_TOKEN). \
-                 Optionally set SLACK_DEFAULT_CHANNEL for a default channel."
+                "Slack integration tools. Requires SLACK_BOT_TOKEN and/or SLACK_USER_TOKEN env vars \
+                 (SLACK_TOKEN is also accepted). Tools act as the bot by default; search_messages \
+                 needs the user token, and tools with an as_user option can switch tokens. \
+                 update_message and delete_message only touch messages this server posted. \
+                 Optionally set SLACK_DEFAULT_CHANNEL for a default channel. Channels, threads, \
+                 canvases and lists are also readable as slack:// resources; channels and threads can be \
+                 subscribed to for updates."
                     .to_string(),
             ),
         }
     }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        let requested = request.protocol_version.clone();
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        let mut info = self.get_info();
+        // Nothing here depends on newer protocol features, so clients that
+        // only speak the previous revision get that one back.
+        if requested == ProtocolVersion::V_2024_11_05 {
+            info.protocol_version = requested;
+        }
+        Ok(info)
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        Self::authorize(&context, &request.name)?;
+        let tcc = ToolCallContext::new(self, request, context);
+        self.tool_router.call(tcc).await
+    }
+
+    /// Only the tools the caller's scopes grant.
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let mut tools = self.tool_router.list_all();
+        if let Some(principal) = auth::principal(&context.extensions) {
+            tools.retain(|tool| principal.scopes.allows(&tool.name));
+        }
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        Self::authorize(&context, auth::RESOURCES)?;
+        let cursor = request.and_then(|r| r.cursor);
+        resources::list(&self.client, cursor).await.map_err(Self::err)
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        Self::authorize(&context, auth::RESOURCES)?;
+        Ok(resources::templates())
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        Self::authorize(&context, auth::RESOURCES)?;
+        resources::read(&self.client, &self.directory, &request.uri).await
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        Self::authorize(&context, auth::RESOURCES)?;
+        self.subscriptions
+            .subscribe(&self.client, &request.uri, context.peer)
+            .await
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        self.subscriptions.unsubscribe(&request.uri);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::{Failure, MockSlack};
+    use serde_json::Value;
+
+    fn json_of(result: CallToolResult) -> Value {
+        let text = &result.content[0].as_text().expect("text content").text;
+        serde_json::from_str(text).expect("JSON tool output")
+    }
+
+    async fn setup() -> (MockSlack, SlackTools) {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general").add_user("U001", "alice");
+        let tools = SlackTools::new(mock.client(), Some("C001".to_string()));
+        (mock, tools)
+    }
+
+    #[tokio::test]
+    async fn post_message_uses_default_channel() {
+        let (mock, tools) = setup().await;
+
+        let data = json_of(
+            tools
+                .post_message(Parameters(PostMessageParams {
+                    channel: None,
+                    text: Some("hello".to_string()),
+                    format: None,
+                    blocks: None,
+                    attachments: None,
+                    as_user: None,
+                }))
+                .await
+                .unwrap(),
+        );
+
+        assert_eq!(data["channel"], "C001");
+        assert_eq!(mock.calls("chat.postMessage")[0]["text"], "hello");
+        assert_eq!(mock.messages("C001")[0]["text"], "hello");
+    }
+
+    #[tokio::test]
+    async fn blocks_are_validated_and_get_fallback_text() {
+        let (mock, tools) = setup().await;
+        let post = |blocks| PostMessageParams {
+            channel: None,
+            text: None,
+            format: None,
+            blocks: Some(blocks),
+            attachments: None,
+            as_user: None,
+        };
+
+        tools
+            .post_message(Parameters(post(json!([
+                { "type": "header", "text": { "type": "plain_text", "text": "Deploy done" } },
+                { "type": "divider" },
+                { "type": "section", "text": { "type": "mrkdwn", "text": "*prod* is green" } },
+            ]))))
+            .await
+            .unwrap();
+        let sent = &mock.calls("chat.postMessage")[0];
+        assert_eq!(sent["text"], "Deploy done\n*prod* is green");
+        assert_eq!(mock.messages("C001")[0]["blocks"][1]["type"], "divider");
+
+        let err = tools
+            .post_message(Parameters(post(json!(
+                r#"[{"type": "divider"}, {"type": "section", "text": "plain string"}]"#
+            ))))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(err.data.unwrap()["path"], "blocks[1].text");
+        assert_eq!(mock.calls("chat.postMessage").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn markdown_is_converted_and_split_into_thread() {
+        let (mock, tools) = setup().await;
+        let markdown: String = (0..60).map(|i| format!("## Step {}\n\n", i)).collect();
+
+        let data = json_of(
+            tools
+                .post_message(Parameters(PostMessageParams {
+                    channel: None,
+                    text: Some(markdown),
+                    format: Some(MessageFormat::Markdown),
+                    blocks: None,
+                    attachments: None,
+                    as_user: None,
+                }))
+                .await
+                .unwrap(),
+        );
+
+        let posts = mock.calls("chat.postMessage");
+        assert_eq!(posts.len(), 2);
+        assert_eq!(posts[0]["blocks"].as_array().unwrap().len(), 50);
+        assert_eq!(posts[0]["blocks"][0]["type"], "header");
+        assert!(posts[0]["text"].as_str().unwrap().starts_with("Step 0\nStep 1"));
+        assert_eq!(posts[1]["thread_ts"], data["ts"]);
+        assert_eq!(posts[1]["blocks"].as_array().unwrap().len(), 10);
+        assert_eq!(data["continued_in_thread"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn schedule_list_and_cancel() {
+        let (mock, tools) = setup().await;
+        let parent = mock.add_message("C001", "U001", "standup thread");
+        let post_at = chrono::Utc::now() + chrono::Duration::days(2);
+
+        let data = json_of(
+            tools
+                .schedule_message(Parameters(ScheduleMessageParams {
+                    channel: Some("#general".to_string()),
+                    post_at: crate::schedule::PostAt::Text(post_at.to_rfc3339()),
+                    text: Some("reminder".to_string()),
+                    format: None,
+                    blocks: None,
+                    attachments: None,
+                    thread_ts: Some(parent.clone()),
+                    reply_broadcast: None,
+                    as_user: None,
+                }))
+                .await
+                .unwrap(),
+        );
+        let sent = &mock.calls("chat.scheduleMessage")[0];
+        assert_eq!(sent["post_at"], post_at.timestamp());
+        assert_eq!(sent["thread_ts"], parent);
+        let id = data["scheduled_message_id"].as_str().unwrap().to_string();
+
+        let list = || ListScheduledMessagesParams {
+            channel: Some("C001".to_string()),
+            limit: None,
+            cursor: None,
+            as_user: None,
+            output: None,
+        };
+        let listed = json_of(tools.list_scheduled_messages(Parameters(list())).await.unwrap());
+        assert_eq!(listed["scheduled_messages"][0]["id"], id);
+        assert!(listed["scheduled_messages"][0]["post_at_utc"].is_string());
+
+        tools
+            .delete_scheduled_message(Parameters(DeleteScheduledMessageParams {
+                channel: "C001".to_string(),
+                scheduled_message_id: id,
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+        let listed = json_of(tools.list_scheduled_messages(Parameters(list())).await.unwrap());
+        assert!(listed["scheduled_messages"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn ephemeral_and_direct_messages() {
+        let (mock, tools) = setup().await;
+        mock.add_user("U002", "bob");
+
+        tools
+            .post_ephemeral(Parameters(PostEphemeralParams {
+                channel: None,
+                user: "@alice".to_string(),
+                text: Some("only you".to_string()),
+                format: None,
+                blocks: None,
+                attachments: None,
+                thread_ts: None,
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+        let sent = &mock.calls("chat.postEphemeral")[0];
+        assert_eq!(sent["channel"], "C001");
+        assert_eq!(sent["user"], "U001");
+
+        let dm = |users: &[&str]| SendDirectMessageParams {
+            users: users.iter().map(|u| u.to_string()).collect(),
+            text: Some("hi".to_string()),
+            format: None,
+            blocks: None,
+            attachments: None,
+            as_user: None,
+        };
+        let data = json_of(tools.send_direct_message(Parameters(dm(&["@bob", "U001"]))).await.unwrap());
+        assert!(data["channel"].as_str().unwrap().starts_with('D'));
+        assert_eq!(mock.calls("conversations.open")[0]["users"], "U001,U002");
+
+        // The group DM is reused rather than reopened.
+        tools.send_direct_message(Parameters(dm(&["U001", "U002"]))).await.unwrap();
+        assert_eq!(mock.calls("conversations.open").len(), 1);
+        assert_eq!(mock.messages(data["channel"].as_str().unwrap()).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn upload_then_download() {
+        let (mock, tools) = setup().await;
+        let upload = |content: Option<&str>, content_base64: Option<&str>, filename: &str| {
+            UploadFileParams {
+                filename: Some(filename.to_string()),
+                content: content.map(String::from),
+                content_base64: content_base64.map(String::from),
+                path: None,
+                title: None,
+                channel: Some("#general".to_string()),
+                thread_ts: None,
+                initial_comment: Some("nightly report".to_string()),
+                alt_text: None,
+                snippet_type: None,
+                as_user: None,
+            }
+        };
+
+        let data = json_of(
+            tools
+                .upload_file(Parameters(upload(Some("a,b\n1,2\n"), None, "report.csv")))
+                .await
+                .unwrap(),
+        );
+        let file_id = data["files"][0]["id"].as_str().unwrap().to_string();
+        assert_eq!(mock.calls("files.getUploadURLExternal")[0]["length"], "8");
+        let complete = &mock.calls("files.completeUploadExternal")[0];
+        assert_eq!(complete["channel_id"], "C001");
+        assert_eq!(mock.encodings("files.completeUploadExternal"), ["form"]);
+
+        let result = tools
+            .download_file(Parameters(DownloadFileParams {
+                file_id: file_id.clone(),
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+        let resource = serde_json::to_value(&result.content[1]).unwrap();
+        assert_eq!(resource["resource"]["text"], "a,b\n1,2\n");
+        assert_eq!(resource["resource"]["uri"], format!("slack://file/{}", file_id));
+
+        let data = json_of(
+            tools
+                .upload_file(Parameters(upload(None, Some("iVBORw0K"), "chart.png")))
+                .await
+                .unwrap(),
+        );
+        let result = tools
+            .download_file(Parameters(DownloadFileParams {
+                file_id: data["files"][0]["id"].as_str().unwrap().to_string(),
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+        let resource = serde_json::to_value(&result.content[1]).unwrap();
+        assert_eq!(resource["resource"]["blob"], "iVBORw0K");
+        assert_eq!(resource["resource"]["mimeType"], "image/png");
+
+        let err = tools
+            .upload_file(Parameters(upload(Some("x"), Some("eA=="), "x.txt")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+
+        // Sessions acting as another Slack identity can't read the server's files.
+        let tenant = tools
+            .with_upload_dir(Some(std::env::temp_dir()))
+            .for_tenant("xoxb-tenant".to_string())
+            .await
+            .unwrap();
+        let mut local = upload(None, None, "x.txt");
+        local.path = Some("x.txt".to_string());
+        let err = tenant.upload_file(Parameters(local)).await.unwrap_err();
+        assert!(err.message.contains("disabled"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn channels_accept_names() {
+        let (mock, tools) = setup().await;
+
+        tools
+            .post_message(Parameters(PostMessageParams {
+                channel: Some("#general".to_string()),
+                text: Some("by name".to_string()),
+                format: None,
+                blocks: None,
+                attachments: None,
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+        tools
+            .post_message(Parameters(PostMessageParams {
+                channel: Some("@alice".to_string()),
+                text: Some("dm".to_string()),
+                format: None,
+                blocks: None,
+                attachments: None,
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+
+        let posts = mock.calls("chat.postMessage");
+        assert_eq!(posts[0]["channel"], "C001");
+        assert!(posts[1]["channel"].as_str().unwrap().starts_with('D'));
+        assert_eq!(mock.calls("conversations.open")[0]["users"], "U001");
+    }
+
+    #[tokio::test]
+    async fn as_user_switches_tokens() {
+        let (mock, tools) = setup().await;
+        let post = |as_user| PostMessageParams {
+            channel: None,
+            text: Some("hi".to_string()),
+            format: None,
+            blocks: None,
+            attachments: None,
+            as_user,
+        };
+
+        tools.post_message(Parameters(post(None))).await.unwrap();
+        tools.post_message(Parameters(post(Some(true)))).await.unwrap();
+        assert_eq!(
+            mock.tokens("chat.postMessage"),
+            [crate::mock_slack::TEST_TOKEN, crate::mock_slack::TEST_USER_TOKEN]
+        );
+
+        let user_only = SlackTools::new(
+            SlackClient::new(None, Some(crate::mock_slack::TEST_USER_TOKEN.to_string()), mock.base_url.clone()),
+            Some("C001".to_string()),
+        );
+        user_only.post_message(Parameters(post(None))).await.unwrap();
+        let err = user_only.post_message(Parameters(post(Some(false)))).await.unwrap_err();
+        assert_eq!(err.code, crate::error::MISSING_TOKEN);
+        assert!(err.message.contains("SLACK_BOT_TOKEN"));
+    }
+
+    #[tokio::test]
+    async fn reply_and_read_thread() {
+        let (mock, tools) = setup().await;
+        let parent = mock.add_message("C001", "U001", "question?");
+
+        tools
+            .reply_to_message(Parameters(ReplyToMessageParams {
+                channel: "C001".to_string(),
+                thread_ts: parent.clone(),
+                text: Some("answer".to_string()),
+                format: None,
+                blocks: None,
+                attachments: None,
+                reply_broadcast: None,
+            as_user: None,
+            }))
+            .await
+            .unwrap();
+
+        let data = json_of(
+            tools
+                .get_thread_replies(Parameters(GetThreadRepliesParams {
+                    channel: "C001".to_string(),
+                    ts: parent,
+                    limit: None,
+                    cursor: None,
+                    max_items: None,
+                    all: None,
+                    output: None,
+                    resolve_names: None,
+                }))
+                .await
+                .unwrap(),
+        );
+        let texts: Vec<&str> = data["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(texts, ["question?", "answer"]);
+    }
+
+    #[tokio::test]
+    async fn edit_and_delete_own_messages() {
+        let (mock, tools) = setup().await;
+        let posted = json_of(
+            tools
+                .post_message(Parameters(PostMessageParams {
+                    channel: Some("#general".to_string()),
+                    text: Some("status: red".to_string()),
+                    format: None,
+                    blocks: None,
+                    attachments: None,
+                    as_user: None,
+                }))
+                .await
+                .unwrap(),
+        );
+        let ts = posted["ts"].as_str().unwrap().to_string();
+
+        tools
+            .update_message(Parameters(UpdateMessageParams {
+                channel: "general".to_string(),
+                ts: ts.clone(),
+                text: Some("status: green".to_string()),
+                format: None,
+                blocks: None,
+                attachments: None,
+                as_user: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(mock.messages("C001")[0]["text"], "status: green");
+
+        let delete = || DeleteMessageParams {
+            channel: "C001".to_string(),
+            ts: ts.clone(),
+            as_user: None,
+        };
+        tools.delete_message(Parameters(delete())).await.unwrap();
+        assert!(mock.messages("C001").is_empty());
+
+        // Once deleted, the message is no longer on record as ours.
+        let err = tools.delete_message(Parameters(delete())).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_edit_unowned_messages() {
+        let (mock, tools) = setup().await;
+        let ts = mock.add_message("C001", "U001", "someone else's");
+        let params = || UpdateMessageParams {
+            channel: "C001".to_string(),
+            ts: ts.clone(),
+            text: Some("hijacked".to_string()),
+            format: None,
+            blocks: None,
+            attachments: None,
+            as_user: None,
+        };
+
+        let err = tools.update_message(Parameters(params())).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(mock.calls("chat.update").is_empty());
+
+        let tools = tools.with_unowned_edits(true);
+        tools.update_message(Parameters(params())).await.unwrap();
+        assert_eq!(mock.messages("C001")[0]["text"], "hijacked");
+    }
+
+    #[tokio::test]
+    async fn list_messages_pages_with_cursor() {
+        let (mock, tools) = setup().await;
+        for i in 0..3 {
+            mock.add_message("C001", "U001", &format!("m{}", i));
+        }
+
+        let data = json_of(
+            tools
+                .list_messages(Parameters(ListMessagesParams {
+                    channel: "C001".to_string(),
+                    limit: Some(2),
+                    cursor: None,
+                    max_items: None,
+                    all: None,
+                    output: None,
+                    resolve_names: None,
+                }))
+                .await
+                .unwrap(),
+        );
+
+        assert_eq!(data["messages"].as_array().unwrap().len(), 2);
+        assert_eq!(data["next_cursor"], "2");
+    }
+
+    #[tokio::test]
+    async fn list_messages_fetches_all_pages() {
+        let (mock, tools) = setup().await;
+        for i in 0..5 {
+            mock.add_message("C001", "U001", &format!("m{}", i));
+        }
+
+        let data = json_of(
+            tools
+                .list_messages(Parameters(ListMessagesParams {
+                    channel: "C001".to_string(),
+                    limit: Some(2),
+                    cursor: None,
+                    max_items: None,
+                    all: Some(true),
+                    output: None,
+                    resolve_names: None,
+                }))
+                .await
+                .unwrap(),
+        );
+
+        assert_eq!(data["messages"].as_array().unwrap().len(), 5);
+        assert_eq!(data["truncated"], false);
+        assert_eq!(data["pages"], 3);
+        assert_eq!(mock.calls("conversations.history").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn output_mode_per_call() {
+        let (mock, tools) = setup().await;
+        mock.add_message("C001", "U001", "hello");
+        let params = |output| ListMessagesParams {
+            channel: "C001".to_string(),
+            limit: None,
+            cursor: None,
+            max_items: None,
+            all: None,
+            output: Some(output),
+            resolve_names: None,
+        };
+
+        let raw = json_of(tools.list_messages(Parameters(params(OutputMode::Raw))).await.unwrap());
+        assert_eq!(raw["ok"], true);
+        assert_eq!(raw["messages"][0]["type"], "message");
+
+        let result = tools.list_messages(Parameters(params(OutputMode::Text))).await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.ends_with("U001: hello"));
+    }
+
+    #[tokio::test]
+    async fn resolve_names_rewrites_ids_and_caches() {
+        let (mock, tools) = setup().await;
+        mock.add_message("C001", "U001", "ping <@U001> in <#C001>");
+        mock.add_message("C001", "U001", "again");
+        let params = || ListMessagesParams {
+            channel: "C001".to_string(),
+            limit: None,
+            cursor: None,
+            max_items: None,
+            all: None,
+            output: None,
+            resolve_names: Some(true),
+        };
+
+        let data = json_of(tools.list_messages(Parameters(params())).await.unwrap());
+        let first = &data["messages"][1];
+        assert_eq!(first["user"], "U001");
+        assert_eq!(first["user_name"], "@alice");
+        assert_eq!(first["text"], "ping @alice in #general");
+
+        tools.list_messages(Parameters(params())).await.unwrap();
+        assert_eq!(mock.calls("users.info").len(), 1);
+        assert_eq!(mock.calls("conversations.info").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn search_uses_user_token_and_modifiers() {
+        let (mock, tools) = setup().await;
+        mock.add_message("C001", "U001", "deploy finished");
+        mock.add_message("C001", "U001", "lunch?");
+
+        let params = || SearchMessagesParams {
+            query: "deploy".to_string(),
+            in_channel: Some("general".to_string()),
+            from_user: Some("U001".to_string()),
+            before: None,
+            after: Some("2024-01-01".to_string()),
+            has: None,
+            sort: Some("timestamp".to_string()),
+            sort_dir: None,
+            count: None,
+            page: None,
+            output: None,
+        };
+
+        let data = json_of(tools.search_messages(Parameters(params())).await.unwrap());
+        assert_eq!(
+            mock.calls("search.messages")[0]["query"],
+            "deploy in:#general from:<@U001> after:2024-01-01"
+        );
+        assert_eq!(with_sigil("C0123ABC".to_string(), '#'), "<#C0123ABC>");
+        assert_eq!(with_sigil("DEV".to_string(), '#'), "#DEV");
+        assert_eq!(mock.tokens("search.messages"), [crate::mock_slack::TEST_USER_TOKEN]);
+        assert_eq!(data["total"], 1);
+        assert_eq!(data["messages"][0]["channel"], "C001");
+        assert_eq!(data["messages"][0]["channel_name"], "general");
+        assert_eq!(data["messages"][0]["text"], "deploy finished");
+
+        let bot_only = SlackTools::new(
+            SlackClient::new(
+                Some(crate::mock_slack::TEST_TOKEN.to_string()),
+                None,
+                mock.base_url.clone(),
+            ),
+            None,
+        );
+        let err = bot_only.search_messages(Parameters(params())).await.unwrap_err();
+        assert_eq!(err.code, crate::error::MISSING_TOKEN);
+    }
+
+    #[tokio::test]
+    async fn list_item_lifecycle() {
+        let (mock, tools) = setup().await;
+
+        let list = json_of(
+            tools
+                .create_list(Parameters(CreateListParams {
+                    name: "Tasks".to_string(),
+                    description: "todo".to_string(),
+                    todo_mode: Some(true),
+                    schema: None,
+                    as_user: Some(true),
+                }))
+                .await
+                .unwrap(),
+        );
+        let list_id = list["list_id"].as_str().unwrap().to_string();
+
+        let item = json_of(
+            tools
+                .create_list_item(Parameters(CreateListItemParams {
+                    list_id: list_id.clone(),
+                    initial_fields: Some(json!({ "Col1": "write tests" })),
+                    as_user: Some(true),
+                }))
+                .await
+                .unwrap(),
+        );
+        let item_id = item["item"]["id"].as_str().unwrap().to_string();
+
+        tools
+            .delete_list_item(Parameters(DeleteListItemParams {
+                list_id: list_id.clone(),
+                id: item_id,
+                as_user: Some(true),
+            }))
+            .await
+            .unwrap();
+        // A list the user owns is changed with the user token throughout.
+        let user = crate::mock_slack::TEST_USER_TOKEN;
+        assert_eq!(mock.tokens("lists.items.create"), [user]);
+        assert_eq!(mock.tokens("lists.items.delete"), [user]);
+
+        let items = json_of(
+            tools
+                .list_list_items(Parameters(ListListItemsParams {
+                    list_id,
+                    limit: None,
+                    cursor: None,
+                    archived: None,
+                    max_items: None,
+                    all: None,
+                    output: None,
+                }))
+                .await
+                .unwrap(),
+        );
+        assert!(items["items"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn create_canvas_in_channel() {
+        let (mock, tools) = setup().await;
+
+        tools
+            .create_canvas(Parameters(CreateCanvasParams {
+                title: "Notes".to_string(),
+                markdown: "# Hi".to_string(),
+                channel_id: Some("C001".to_string()),
+            as_user: None,
+            }))
+            .await
+            .unwrap();
+
+        assert!(mock.calls("canvases.create").is_empty());
+        let calls = mock.calls("conversations.canvases.create");
+        assert_eq!(calls[0]["document_content"]["markdown"], "# Hi");
+    }
+
+    #[tokio::test]
+    async fn slack_errors_become_tool_errors() {
+        let (mock, tools) = setup().await;
+        mock.fail_next("reactions.add", Failure::Error("already_reacted"));
+
+        let err = tools
+            .add_reaction(Parameters(AddReactionParams {
+                channel: "C001".to_string(),
+                timestamp: "1.0".to_string(),
+                name: "thumbsup".to_string(),
+            as_user: None,
+            }))
+            .await
+            .unwrap_err();
+
+        assert!(err.message.contains("already_reacted"));
+        assert_eq!(err.code, crate::error::SLACK_API);
+    }
+
+    #[tokio::test]
+    async fn not_in_channel_carries_join_hint() {
+        let (mock, tools) = setup().await;
+        mock.fail_next("conversations.history", Failure::Error("not_in_channel"));
+
+        let err = tools
+            .list_messages(Parameters(ListMessagesParams {
+                channel: "C001".to_string(),
+                limit: None,
+                cursor: None,
+                max_items: None,
+                all: None,
+                output: None,
+                resolve_names: None,
+            }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, crate::error::NOT_IN_CHANNEL);
+        let data = err.data.unwrap();
+        assert_eq!(data["channel"], "C001");
+        assert!(data["hint"].as_str().unwrap().contains("join_channel"));
+
+        tools
+            .join_channel(Parameters(JoinChannelParams {
+                channel: "C001".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(mock.calls("conversations.join")[0]["channel"], "C001");
+    }
+
+    /// Serve `tools` to an in-process MCP client, for tools that need the
+    /// request context (progress, cancellation).
+    async fn connect(tools: SlackTools) -> rmcp::service::RunningService<rmcp::RoleClient, ()> {
+        use rmcp::ServiceExt;
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move { tools.serve(server_io).await.unwrap().waiting().await });
+        ().serve(client_io).await.unwrap()
+    }
+
+    fn call(name: &str, arguments: Value) -> CallToolRequestParam {
+        CallToolRequestParam {
+            name: name.to_string().into(),
+            arguments: arguments.as_object().cloned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn request_approval_waits_for_a_click_and_updates_the_message() {
+        use crate::events::{EventBus, SlackEvent};
+
+        let (mock, tools) = setup().await;
+        let args = json!({ "question": "Deploy v2?", "approvers": ["U001"] });
+        let err = connect(tools.clone())
+            .await
+            .call_tool(call("request_approval", args.clone()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("SLACK_APP_TOKEN"), "{}", err);
+
+        let bus = EventBus::new();
+        let client = connect(tools.with_events(Some(bus.clone()))).await;
+        let call = tokio::spawn(async move { client.call_tool(call("request_approval", args)).await });
+
+        let (ts, id) = loop {
+            if let Some(m) = mock.messages("C001").first() {
+                let id = m["blocks"].as_array().unwrap().last().unwrap()["elements"][0]["value"]
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+                break (m["ts"].as_str().unwrap().to_string(), id);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        };
+        let click = |user: &str, action: &str| {
+            SlackEvent::Interaction(json!({
+                "type": "block_actions",
+                "user": { "id": user, "username": user },
+                "channel": { "id": "C001" },
+                "container": { "type": "message", "message_ts": ts, "channel_id": "C001" },
+                "actions": [{ "action_id": action, "value": id }],
+                "state": { "values": { "approval_comment": { "comment": { "value": "go" } } } },
+            }))
+        };
+        // Not an approver: ignored.
+        bus.publish(click("U002", "approval_reject"));
+        bus.publish(click("U001", "approval_approve"));
+
+        let data = json_of(call.await.unwrap().unwrap());
+        assert_eq!(data["decision"], "approved");
+        assert_eq!(data["approver"]["id"], "U001");
+        assert_eq!(data["comment"], "go");
+        assert_eq!(data["ts"], ts.as_str());
+
+        let message = &mock.messages("C001")[0];
+        let outcome = message["blocks"][1]["elements"][0]["text"].as_str().unwrap();
+        assert!(outcome.contains("Approved by <@U001>: go"), "{}", outcome);
+    }
 }
@@ -2,30 +2,260 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::markdown::MessageFormat;
+use crate::schedule::PostAt;
+use crate::shape::OutputMode;
+
 // ─── Messaging ──────────────────────────────────────────
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct PostMessageParams {
-    #[schemars(description = "Slack channel ID (e.g. C0123456789). Falls back to SLACK_DEFAULT_CHANNEL env var if omitted.")]
+    #[schemars(description = "Channel ID (e.g. C0123456789), #channel-name, or @user to send a DM. Falls back to SLACK_DEFAULT_CHANNEL env var if omitted.")]
     pub channel: Option<String>,
 
-    #[schemars(description = "Message text. Supports Slack mrkdwn formatting.")]
-    pub text: String,
+    #[schemars(description = "Message text. Supports Slack mrkdwn formatting. Optional when blocks or attachments are given; it then defaults to a plain-text rendering of them, used for notifications.")]
+    pub text: Option<String>,
+
+    #[schemars(description = "How to read `text`: 'markdown' (CommonMark, converted to Slack blocks: headings, lists, code, tables), 'mrkdwn' (Slack's own markup, sent as-is; the default) or 'plain' (no formatting). Text too long for one message is split, with the remainder posted as thread replies.")]
+    pub format: Option<MessageFormat>,
+
+    #[schemars(description = "Optional Block Kit blocks (a JSON array, or a JSON-encoded string of one), e.g. section, context, divider, actions. Validated locally before sending; at most 50 blocks.")]
+    pub blocks: Option<Value>,
+
+    #[schemars(description = "Optional legacy attachments (a JSON array, or a JSON-encoded string of one), e.g. for a colored sidebar. Attachments may contain their own blocks.")]
+    pub attachments: Option<Value>,
+
+    #[schemars(description = "If true, post as the user who installed the app (requires SLACK_USER_TOKEN); if false, post as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ReplyToMessageParams {
-    #[schemars(description = "Slack channel ID where the parent message lives.")]
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) where the parent message lives.")]
     pub channel: String,
 
     #[schemars(description = "Timestamp (ts) of the parent message to reply to.")]
     pub thread_ts: String,
 
-    #[schemars(description = "Reply message text. Supports Slack mrkdwn formatting.")]
-    pub text: String,
+    #[schemars(description = "Reply message text. Supports Slack mrkdwn formatting. Optional when blocks or attachments are given; it then defaults to a plain-text rendering of them, used for notifications.")]
+    pub text: Option<String>,
+
+    #[schemars(description = "How to read `text`: 'markdown' (CommonMark, converted to Slack blocks: headings, lists, code, tables), 'mrkdwn' (Slack's own markup, sent as-is; the default) or 'plain' (no formatting). Text too long for one message is split, with the remainder posted as thread replies.")]
+    pub format: Option<MessageFormat>,
+
+    #[schemars(description = "Optional Block Kit blocks (a JSON array, or a JSON-encoded string of one), e.g. section, context, divider, actions. Validated locally before sending; at most 50 blocks.")]
+    pub blocks: Option<Value>,
+
+    #[schemars(description = "Optional legacy attachments (a JSON array, or a JSON-encoded string of one), e.g. for a colored sidebar. Attachments may contain their own blocks.")]
+    pub attachments: Option<Value>,
 
     #[schemars(description = "If true, also post the reply to the channel (not just the thread). Defaults to false.")]
     pub reply_broadcast: Option<bool>,
+
+    #[schemars(description = "If true, reply as the user who installed the app (requires SLACK_USER_TOKEN); if false, reply as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UpdateMessageParams {
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) where the message lives.")]
+    pub channel: String,
+
+    #[schemars(description = "Timestamp (ts) of the message to edit, as returned when it was posted.")]
+    pub ts: String,
+
+    #[schemars(description = "New message text. Supports Slack mrkdwn formatting. Optional when blocks or attachments are given; it then defaults to a plain-text rendering of them, used for notifications.")]
+    pub text: Option<String>,
+
+    #[schemars(description = "How to read `text`: 'markdown' (CommonMark, converted to Slack blocks: headings, lists, code, tables), 'mrkdwn' (Slack's own markup, sent as-is; the default) or 'plain' (no formatting). The result must fit in one message.")]
+    pub format: Option<MessageFormat>,
+
+    #[schemars(description = "Optional Block Kit blocks replacing the message's current ones (a JSON array, or a JSON-encoded string of one), e.g. section, context, divider, actions. Validated locally before sending; at most 50 blocks.")]
+    pub blocks: Option<Value>,
+
+    #[schemars(description = "Optional legacy attachments replacing the message's current ones (a JSON array, or a JSON-encoded string of one), e.g. for a colored sidebar. Attachments may contain their own blocks.")]
+    pub attachments: Option<Value>,
+
+    #[schemars(description = "If true, edit with the user token (the message must have been posted as that user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeleteMessageParams {
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) where the message lives.")]
+    pub channel: String,
+
+    #[schemars(description = "Timestamp (ts) of the message to delete.")]
+    pub ts: String,
+
+    #[schemars(description = "If true, delete with the user token (the message must have been posted as that user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PostEphemeralParams {
+    #[schemars(description = "Channel ID, #channel-name or @user where the message appears. Falls back to SLACK_DEFAULT_CHANNEL env var if omitted.")]
+    pub channel: Option<String>,
+
+    #[schemars(description = "The only user who will see the message: a user ID (U0123…) or @name. They must be a member of the channel.")]
+    pub user: String,
+
+    #[schemars(description = "Message text. Optional when blocks or attachments are given.")]
+    pub text: Option<String>,
+
+    #[schemars(description = "How to read `text`: 'markdown', 'mrkdwn' (default) or 'plain'. The result must fit in one message.")]
+    pub format: Option<MessageFormat>,
+
+    #[schemars(description = "Optional Block Kit blocks (a JSON array, or a JSON-encoded string of one).")]
+    pub blocks: Option<Value>,
+
+    #[schemars(description = "Optional legacy attachments (a JSON array, or a JSON-encoded string of one).")]
+    pub attachments: Option<Value>,
+
+    #[schemars(description = "Timestamp (ts) of a parent message, to show the message in that thread.")]
+    pub thread_ts: Option<String>,
+
+    #[schemars(description = "If true, send as the user who installed the app (requires SLACK_USER_TOKEN); if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SendDirectMessageParams {
+    #[schemars(description = "Recipients as user IDs (U0123…) or @names. One user opens a DM; several open a group DM (up to 8 users).")]
+    pub users: Vec<String>,
+
+    #[schemars(description = "Message text. Optional when blocks or attachments are given.")]
+    pub text: Option<String>,
+
+    #[schemars(description = "How to read `text`: 'markdown', 'mrkdwn' (default) or 'plain'. Text too long for one message is split, with the remainder posted as thread replies.")]
+    pub format: Option<MessageFormat>,
+
+    #[schemars(description = "Optional Block Kit blocks (a JSON array, or a JSON-encoded string of one).")]
+    pub blocks: Option<Value>,
+
+    #[schemars(description = "Optional legacy attachments (a JSON array, or a JSON-encoded string of one).")]
+    pub attachments: Option<Value>,
+
+    #[schemars(description = "If true, send as the user who installed the app (requires SLACK_USER_TOKEN); if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+// ─── Scheduled messages ─────────────────────────────────
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ScheduleMessageParams {
+    #[schemars(description = "Channel ID, #channel-name, or @user to send a DM. Falls back to SLACK_DEFAULT_CHANNEL env var if omitted.")]
+    pub channel: Option<String>,
+
+    #[schemars(description = "When to post: a Unix timestamp (seconds), or an ISO-8601 time with a time zone, e.g. 2025-06-02T09:00:00-04:00 or 2025-06-02T13:00Z. Must be in the future and at most 120 days ahead.")]
+    pub post_at: PostAt,
+
+    #[schemars(description = "Message text. Optional when blocks or attachments are given.")]
+    pub text: Option<String>,
+
+    #[schemars(description = "How to read `text`: 'markdown', 'mrkdwn' (default) or 'plain'. The result must fit in one message.")]
+    pub format: Option<MessageFormat>,
+
+    #[schemars(description = "Optional Block Kit blocks (a JSON array, or a JSON-encoded string of one).")]
+    pub blocks: Option<Value>,
+
+    #[schemars(description = "Optional legacy attachments (a JSON array, or a JSON-encoded string of one).")]
+    pub attachments: Option<Value>,
+
+    #[schemars(description = "Timestamp (ts) of a parent message, to schedule a thread reply.")]
+    pub thread_ts: Option<String>,
+
+    #[schemars(description = "With thread_ts: also post the reply to the channel. Defaults to false.")]
+    pub reply_broadcast: Option<bool>,
+
+    #[schemars(description = "If true, schedule as the user who installed the app (requires SLACK_USER_TOKEN); if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListScheduledMessagesParams {
+    #[schemars(description = "Only list messages scheduled in this channel (ID, #channel-name or @user). Lists all channels if omitted.")]
+    pub channel: Option<String>,
+
+    #[schemars(description = "Maximum number of scheduled messages to return. Default 100.")]
+    pub limit: Option<u32>,
+
+    #[schemars(description = "Pagination cursor from a previous response.")]
+    pub cursor: Option<String>,
+
+    #[schemars(description = "If true, list messages scheduled with the user token; if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+
+    #[schemars(description = "Response format: 'compact', 'text' or 'raw'. Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DeleteScheduledMessageParams {
+    #[schemars(description = "Channel ID, #channel-name or @user the message is scheduled in.")]
+    pub channel: String,
+
+    #[schemars(description = "The scheduled_message_id returned by schedule_message or list_scheduled_messages.")]
+    pub scheduled_message_id: String,
+
+    #[schemars(description = "If true, cancel with the user token (for messages scheduled as that user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+// ─── Files ──────────────────────────────────────────────
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct UploadFileParams {
+    #[schemars(description = "File name including extension, e.g. report.csv. Required unless `path` is given, in which case it defaults to the file's own name.")]
+    pub filename: Option<String>,
+
+    #[schemars(description = "File contents as text (e.g. CSV, Markdown, source code). Give exactly one of content, content_base64 or path.")]
+    pub content: Option<String>,
+
+    #[schemars(description = "File contents as base64 (or a data: URL), for binary files such as images or PDFs.")]
+    pub content_base64: Option<String>,
+
+    #[schemars(description = "Path of a local file to upload, relative to the server's SLACK_UPLOAD_DIR. Files outside that directory are refused.")]
+    pub path: Option<String>,
+
+    #[schemars(description = "Title shown in Slack. Defaults to the file name.")]
+    pub title: Option<String>,
+
+    #[schemars(description = "Channel ID, #channel-name or @user to share the file in. If omitted, the file is uploaded privately without being shared.")]
+    pub channel: Option<String>,
+
+    #[schemars(description = "Timestamp (ts) of a parent message, to share the file as a thread reply. Requires channel.")]
+    pub thread_ts: Option<String>,
+
+    #[schemars(description = "Message text posted along with the file.")]
+    pub initial_comment: Option<String>,
+
+    #[schemars(description = "Description of an image, for screen readers.")]
+    pub alt_text: Option<String>,
+
+    #[schemars(description = "Syntax type for text snippets, e.g. python, javascript, csv.")]
+    pub snippet_type: Option<String>,
+
+    #[schemars(description = "If true, upload as the user who installed the app (requires SLACK_USER_TOKEN); if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetFileInfoParams {
+    #[schemars(description = "The file ID, e.g. F0123456789.")]
+    pub file_id: String,
+
+    #[schemars(description = "Response format: 'compact', 'text' or 'raw'. Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DownloadFileParams {
+    #[schemars(description = "The file ID, e.g. F0123456789.")]
+    pub file_id: String,
+
+    #[schemars(description = "If true, download with the user token (for files only that user can see); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 // ─── Canvas ─────────────────────────────────────────────
@@ -38,8 +268,11 @@ pub struct CreateCanvasParams {
     #[schemars(description = "Markdown content for the canvas body.")]
     pub markdown: String,
 
-    #[schemars(description = "Optional channel ID. If provided, creates a channel-bound canvas; otherwise creates a standalone canvas.")]
+    #[schemars(description = "Optional channel ID or #channel-name. If provided, creates a channel-bound canvas; otherwise creates a standalone canvas.")]
     pub channel_id: Option<String>,
+
+    #[schemars(description = "If true, create the canvas as the user who installed the app (requires SLACK_USER_TOKEN), so it is owned by that user; if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -49,13 +282,16 @@ pub struct UpdateCanvasParams {
 
     #[schemars(description = "New markdown content to replace the entire canvas body.")]
     pub markdown: String,
+
+    #[schemars(description = "If true, edit with the user token (needed for canvases owned by that user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 // ─── Channels ───────────────────────────────────────────
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListChannelsParams {
-    #[schemars(description = "Maximum number of channels to return. Default 100, max 1000.")]
+    #[schemars(description = "Maximum number of channels to return (page size when paginating). Default 100, max 1000.")]
     pub limit: Option<u32>,
 
     #[schemars(description = "Pagination cursor returned from a previous call.")]
@@ -63,40 +299,164 @@ pub struct ListChannelsParams {
 
     #[schemars(description = "Comma-separated channel types: public_channel, private_channel, mpim, im. Default: public_channel.")]
     pub types: Option<String>,
+
+    #[schemars(description = "Follow pagination cursors and return up to this many channels in one response. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub max_items: Option<u32>,
+
+    #[schemars(description = "If true, follow pagination cursors until every channel is fetched or the response size budget is reached. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub all: Option<bool>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct JoinChannelParams {
+    #[schemars(description = "ID or #name of the public channel to join.")]
+    pub channel: String,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ListMessagesParams {
-    #[schemars(description = "Channel ID to fetch message history from.")]
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) to fetch message history from.")]
     pub channel: String,
 
-    #[schemars(description = "Maximum number of messages to return. Default 20, max 1000.")]
+    #[schemars(description = "Maximum number of messages to return (page size when paginating). Default 20, max 1000.")]
     pub limit: Option<u32>,
 
     #[schemars(description = "Pagination cursor from a previous response.")]
     pub cursor: Option<String>,
+
+    #[schemars(description = "Follow pagination cursors and return up to this many messages in one response. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub max_items: Option<u32>,
+
+    #[schemars(description = "If true, follow pagination cursors until every message is fetched or the response size budget is reached. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub all: Option<bool>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+
+    #[schemars(description = "If true, add a `user_name` to each message and rewrite <@U…>/<#C…> mentions in the text to @display_name/#channel-name. Costs extra Slack lookups the first time each ID is seen.")]
+    pub resolve_names: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetThreadRepliesParams {
-    #[schemars(description = "Channel ID containing the thread.")]
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) containing the thread.")]
     pub channel: String,
 
     #[schemars(description = "Timestamp (ts) of the parent message.")]
     pub ts: String,
 
-    #[schemars(description = "Maximum number of replies to return. Default 100, max 1000.")]
+    #[schemars(description = "Maximum number of replies to return (page size when paginating). Default 100, max 1000.")]
     pub limit: Option<u32>,
 
     #[schemars(description = "Pagination cursor from a previous response.")]
     pub cursor: Option<String>,
+
+    #[schemars(description = "Follow pagination cursors and return up to this many replies in one response. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub max_items: Option<u32>,
+
+    #[schemars(description = "If true, follow pagination cursors until every reply is fetched or the response size budget is reached. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub all: Option<bool>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+
+    #[schemars(description = "If true, add a `user_name` to each message and rewrite <@U…>/<#C…> mentions in the text to @display_name/#channel-name. Costs extra Slack lookups the first time each ID is seen.")]
+    pub resolve_names: Option<bool>,
+}
+
+// ─── Waiting on people ──────────────────────────────────
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct WaitForReplyParams {
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) containing the thread.")]
+    pub channel: String,
+
+    #[schemars(description = "Timestamp (ts) of the thread's parent message.")]
+    pub thread_ts: String,
+
+    #[schemars(description = "Only count replies from this user (ID or @name).")]
+    pub user: Option<String>,
+
+    #[schemars(description = "Only count replies whose text matches this regular expression, e.g. '(?i)^(yes|no)\\b'. Matches any reply if omitted.")]
+    pub pattern: Option<String>,
+
+    #[schemars(description = "How long to wait, in seconds. Default 300, max 3600. Make sure your MCP client's request timeout is at least this long.")]
+    pub timeout_seconds: Option<u64>,
+
+    #[schemars(description = "Only count replies posted after this ts, e.g. the ts of the question you posted. Defaults to the newest message in the thread when the wait starts.")]
+    pub after_ts: Option<String>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RequestApprovalParams {
+    #[schemars(description = "Channel ID, #channel-name or @user (DM) to ask in. Uses SLACK_DEFAULT_CHANNEL if omitted.")]
+    pub channel: Option<String>,
+
+    #[schemars(description = "What needs approving, in one line (mrkdwn), e.g. 'Deploy v2.3 to production?'.")]
+    pub question: String,
+
+    #[schemars(description = "Optional details shown under the question (mrkdwn): what will happen, what could go wrong.")]
+    pub details: Option<String>,
+
+    #[schemars(description = "Users (IDs or @names) whose answer counts. Anyone in the channel can answer if omitted.")]
+    pub approvers: Option<Vec<String>>,
+
+    #[schemars(description = "Post the request as a reply in this thread.")]
+    pub thread_ts: Option<String>,
+
+    #[schemars(description = "How long to wait for an answer, in seconds, before the request expires. Default 900, max 3600. Make sure your MCP client's request timeout is at least this long.")]
+    pub timeout_seconds: Option<u64>,
+}
+
+// ─── Search ─────────────────────────────────────────────
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchMessagesParams {
+    #[schemars(description = "Search query. May include Slack modifiers directly, e.g. 'deploy in:#ops from:@alice after:2024-01-01 has:link'.")]
+    pub query: String,
+
+    #[schemars(description = "Only match messages in this channel (adds in:). Accepts #channel-name or a channel ID.")]
+    pub in_channel: Option<String>,
+
+    #[schemars(description = "Only match messages from this user (adds from:). Accepts @handle or a user ID.")]
+    pub from_user: Option<String>,
+
+    #[schemars(description = "Only match messages before this date, YYYY-MM-DD (adds before:).")]
+    pub before: Option<String>,
+
+    #[schemars(description = "Only match messages after this date, YYYY-MM-DD (adds after:).")]
+    pub after: Option<String>,
+
+    #[schemars(description = "Only match messages that have this, e.g. 'link', 'reaction', 'pin', 'file' (adds has:).")]
+    pub has: Option<String>,
+
+    #[schemars(description = "Sort by 'score' (relevance, default) or 'timestamp'.")]
+    pub sort: Option<String>,
+
+    #[schemars(description = "Sort direction: 'desc' (default) or 'asc'.")]
+    pub sort_dir: Option<String>,
+
+    #[schemars(description = "Results per page. Default 20, max 100.")]
+    pub count: Option<u32>,
+
+    #[schemars(description = "Page number, starting at 1.")]
+    pub page: Option<u32>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
 }
 
 // ─── Reactions ──────────────────────────────────────────
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct AddReactionParams {
-    #[schemars(description = "Channel ID where the message to react to was posted.")]
+    #[schemars(description = "Channel ID or #channel-name where the message to react to was posted.")]
     pub channel: String,
 
     #[schemars(description = "Timestamp of the message to add a reaction to.")]
@@ -104,17 +464,29 @@ pub struct AddReactionParams {
 
     #[schemars(description = "Emoji name without colons (e.g. 'thumbsup', not ':thumbsup:').")]
     pub name: String,
+
+    #[schemars(description = "If true, react as the user who installed the app (requires SLACK_USER_TOKEN); if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 // ─── Users ──────────────────────────────────────────────
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetUsersParams {
-    #[schemars(description = "Maximum number of users to return. Default 100, max 1000.")]
+    #[schemars(description = "Maximum number of users to return (page size when paginating). Default 100, max 1000.")]
     pub limit: Option<u32>,
 
     #[schemars(description = "Pagination cursor from a previous response.")]
     pub cursor: Option<String>,
+
+    #[schemars(description = "Follow pagination cursors and return up to this many users in one response. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub max_items: Option<u32>,
+
+    #[schemars(description = "If true, follow pagination cursors until every user is fetched or the response size budget is reached. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub all: Option<bool>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
 }
 
 // ─── Lists ──────────────────────────────────────────────
@@ -132,6 +504,9 @@ pub struct CreateListParams {
 
     #[schemars(description = "Column schema definition for the list. A JSON object whose keys are column IDs and values describe each column (type, label, options, etc.). Omit to create a simple list with default columns.")]
     pub schema: Option<Value>,
+
+    #[schemars(description = "If true, create the list as the user who installed the app (requires SLACK_USER_TOKEN), so it is owned by that user; if false, as the bot. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -147,6 +522,9 @@ pub struct UpdateListParams {
 
     #[schemars(description = "Enable or disable to-do mode.")]
     pub todo_mode: Option<bool>,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -156,6 +534,9 @@ pub struct CreateListItemParams {
 
     #[schemars(description = "JSON object of initial field values keyed by column ID. Values depend on column type (text, number, date, user, etc.).")]
     pub initial_fields: Option<Value>,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -163,7 +544,7 @@ pub struct ListListItemsParams {
     #[schemars(description = "The list ID to fetch items from.")]
     pub list_id: String,
 
-    #[schemars(description = "Maximum number of items to return.")]
+    #[schemars(description = "Maximum number of items to return (page size when paginating).")]
     pub limit: Option<u32>,
 
     #[schemars(description = "Pagination cursor from a previous response.")]
@@ -171,6 +552,15 @@ pub struct ListListItemsParams {
 
     #[schemars(description = "If true, include archived items.")]
     pub archived: Option<bool>,
+
+    #[schemars(description = "Follow pagination cursors and return up to this many items in one response. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub max_items: Option<u32>,
+
+    #[schemars(description = "If true, follow pagination cursors until every item is fetched or the response size budget is reached. The result reports `truncated` and a `next_cursor` to resume from.")]
+    pub all: Option<bool>,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -180,6 +570,12 @@ pub struct GetListItemParams {
 
     #[schemars(description = "The item ID to retrieve.")]
     pub id: String,
+
+    #[schemars(description = "Response format: 'compact' (key fields only), 'text' (one line per entry) or 'raw' (full Slack payload). Defaults to the server's SLACK_OUTPUT_MODE.")]
+    pub output: Option<OutputMode>,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -189,6 +585,9 @@ pub struct UpdateListItemParams {
 
     #[schemars(description = "JSON object of field values to update, keyed by column ID.")]
     pub cells: Value,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -198,6 +597,9 @@ pub struct DeleteListItemParams {
 
     #[schemars(description = "The item ID to delete.")]
     pub id: String,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -207,6 +609,9 @@ pub struct DeleteListItemsParams {
 
     #[schemars(description = "Array of item IDs to delete.")]
     pub ids: Value,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -222,6 +627,9 @@ pub struct SetListAccessParams {
 
     #[schemars(description = "User IDs to grant access to.")]
     pub user_ids: Option<Value>,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -234,4 +642,7 @@ pub struct DeleteListAccessParams {
 
     #[schemars(description = "User IDs to revoke access from.")]
     pub user_ids: Option<Value>,
+
+    #[schemars(description = "If true, act with the user token (needed for lists created with as_user); if false, with the bot token. Defaults to the bot token when configured.")]
+    pub as_user: Option<bool>,
 }
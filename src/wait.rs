@@ -0,0 +1,219 @@
+//! Waiting for a matching reply in a thread: follows the event bus when
+//! there is one, and polls conversations.replies with backoff.
+
+use std::time::Duration;
+
+use regex::Regex;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+
+use crate::error::SlackError;
+use crate::events::{ts_key, EventBus, SlackEvent};
+use crate::slack_client::{PageLimits, SlackClient};
+
+/// wait_for_reply's timeout when none is given, and the most it accepts.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// How often a waiting tool call reports progress.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Polling starts at the first delay and doubles up to the second.
+const MIN_POLL: Duration = Duration::from_secs(2);
+const MAX_POLL: Duration = Duration::from_secs(30);
+
+/// Which replies count.
+pub struct ReplyFilter {
+    pub channel: String,
+    pub thread_ts: String,
+    /// Only replies posted after this ts.
+    pub after: String,
+    /// Only replies from this user ID.
+    pub user: Option<String>,
+    /// Only replies whose text matches.
+    pub pattern: Option<Regex>,
+}
+
+impl ReplyFilter {
+    pub fn matches(&self, message: &Value) -> bool {
+        let Some(ts) = message["ts"].as_str() else {
+            return false;
+        };
+        // Edits, deletions and join notices are not replies.
+        let is_reply = matches!(
+            message["subtype"].as_str(),
+            None | Some("thread_broadcast") | Some("file_share")
+        );
+        is_reply
+            && message["thread_ts"].as_str() == Some(self.thread_ts.as_str())
+            && ts != self.thread_ts
+            && ts_key(ts) > ts_key(&self.after)
+            && self
+                .user
+                .as_ref()
+                .is_none_or(|u| message["user"].as_str() == Some(u.as_str()))
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|p| p.is_match(message["text"].as_str().unwrap_or_default()))
+    }
+}
+
+/// The ts of the newest message in a thread, for waits that start "now".
+pub async fn latest_in_thread(
+    client: &SlackClient,
+    channel: &str,
+    thread_ts: &str,
+) -> Result<String, SlackError> {
+    let page = client
+        .paginate(
+            "conversations.replies",
+            json!({ "channel": channel, "ts": thread_ts }),
+            "messages",
+            PageLimits::default(),
+        )
+        .await?;
+    Ok(page
+        .items
+        .iter()
+        .filter_map(|m| m["ts"].as_str())
+        .max_by_key(|ts| ts_key(ts))
+        .unwrap_or(thread_ts)
+        .to_string())
+}
+
+/// The first matching reply already in the thread, if any.
+async fn poll(client: &SlackClient, filter: &ReplyFilter) -> Result<Option<Value>, SlackError> {
+    let page = client
+        .paginate(
+            "conversations.replies",
+            json!({ "channel": filter.channel, "ts": filter.thread_ts, "oldest": filter.after }),
+            "messages",
+            PageLimits::default(),
+        )
+        .await?;
+    Ok(page.items.into_iter().find(|m| filter.matches(m)))
+}
+
+/// Wait until a reply matching `filter` shows up and return it. Runs until
+/// it finds one; callers bound it with a timeout.
+pub async fn next_reply(
+    client: &SlackClient,
+    events: Option<&EventBus>,
+    filter: &ReplyFilter,
+) -> Result<Value, SlackError> {
+    // Listen before the first poll so nothing slips between the two.
+    let mut rx = events.map(|bus| bus.subscribe());
+    if let Some(reply) = poll(client, filter).await? {
+        return Ok(reply);
+    }
+
+    // The bus may miss a reply (a dropped connection, an unsubscribed
+    // channel), so keep polling behind it, just less often.
+    let mut delay = if rx.is_some() { MAX_POLL } else { MIN_POLL };
+    let mut next_poll = Instant::now() + delay;
+    loop {
+        let event = match rx.as_mut() {
+            Some(events) => tokio::select! {
+                event = events.recv() => Some(event),
+                _ = tokio::time::sleep_until(next_poll) => None,
+            },
+            None => {
+                tokio::time::sleep_until(next_poll).await;
+                None
+            }
+        };
+        match event {
+            Some(Ok(event @ SlackEvent::Message(_))) => {
+                if event.channel() == Some(filter.channel.as_str()) && filter.matches(event.payload())
+                {
+                    return Ok(event.payload().clone());
+                }
+            }
+            Some(Ok(_)) => {}
+            Some(Err(RecvError::Closed)) => rx = None,
+            // Time to poll, or some events were missed and one of them may
+            // have been the reply.
+            None | Some(Err(RecvError::Lagged(_))) => {
+                if let Some(reply) = poll(client, filter).await? {
+                    return Ok(reply);
+                }
+                delay = (delay * 2).min(MAX_POLL);
+                next_poll = Instant::now() + delay;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_slack::MockSlack;
+
+    fn filter(thread_ts: &str, after: &str) -> ReplyFilter {
+        ReplyFilter {
+            channel: "C001".to_string(),
+            thread_ts: thread_ts.to_string(),
+            after: after.to_string(),
+            user: Some("U001".to_string()),
+            pattern: Some(Regex::new("(?i)^(yes|no)\\b").unwrap()),
+        }
+    }
+
+    #[test]
+    fn filters_replies() {
+        let f = filter("1.000001", "1.000002");
+        let reply = |ts: &str, user: &str, text: &str| {
+            json!({ "ts": ts, "thread_ts": "1.000001", "user": user, "text": text })
+        };
+        assert!(f.matches(&reply("1.000003", "U001", "Yes, ship it")));
+        assert!(!f.matches(&reply("1.000002", "U001", "yes")), "not after the watermark");
+        assert!(!f.matches(&reply("1.000003", "U002", "yes")), "wrong user");
+        assert!(!f.matches(&reply("1.000003", "U001", "maybe")), "pattern");
+        let mut edited = reply("1.000003", "U001", "yes");
+        edited["subtype"] = json!("message_changed");
+        assert!(!f.matches(&edited));
+    }
+
+    #[tokio::test]
+    async fn finds_replies_by_polling_and_from_events() {
+        let mock = MockSlack::start().await;
+        mock.add_channel("C001", "general");
+        let parent = mock.add_message("C001", "U001", "deploy?");
+        let client = mock.client();
+        let reply = |text: &str| {
+            client.post(
+                "chat.postMessage",
+                json!({ "channel": "C001", "thread_ts": parent, "text": text }),
+            )
+        };
+
+        // The mock's replies come from the bot, so match any user here.
+        let mut f = filter(&parent, &latest_in_thread(&client, "C001", &parent).await.unwrap());
+        f.user = None;
+        reply("not yet").await.unwrap();
+        let ts = reply("yes").await.unwrap()["ts"].as_str().unwrap().to_string();
+        let found = next_reply(&client, None, &f).await.unwrap();
+        assert_eq!(found["ts"], ts.as_str());
+
+        f.after = ts;
+        let bus = EventBus::new();
+        let publisher = bus.clone();
+        let thread = parent.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            for (ts, text) in [("1800000000.000001", "hmm"), ("1800000000.000002", "no")] {
+                publisher.publish(SlackEvent::Message(json!({
+                    "type": "message", "channel": "C001", "ts": ts, "thread_ts": thread,
+                    "user": "U001", "text": text,
+                })));
+            }
+        });
+        let found = tokio::time::timeout(Duration::from_secs(5), next_reply(&client, Some(&bus), &f))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found["text"], "no");
+    }
+}